@@ -0,0 +1,85 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/bit-array-rs
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use std::fmt::Write;
+use std::hint::black_box;
+
+use bit_array_rs::BitArray;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+
+fn iter_set_bits(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iter_set_bits");
+    for (name, array) in [
+        ("dense", BitArray::with_all_set(1 << 16)),
+        ("sparse", BitArray::from_fn(1 << 16, |i| i % 97 == 0)),
+    ] {
+        group.bench_function(format!("{name}/per_set_bit"), |b| {
+            b.iter(|| black_box(&array).iter_set_bits().sum::<usize>());
+        });
+        group.bench_function(format!("{name}/naive_per_bit"), |b| {
+            b.iter(|| {
+                let array = black_box(&array);
+                (0..array.bit_count())
+                    .filter(|&i| array.get(i))
+                    .sum::<usize>()
+            });
+        });
+    }
+    group.finish();
+}
+
+fn resize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resize");
+    let base = BitArray::with_all_set((1 << 20) + 3);
+    group.bench_function("grow_filled", |b| {
+        b.iter_batched_ref(
+            || base.clone(),
+            |array| array.resize(black_box(1 << 24), true),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("grow_cleared", |b| {
+        b.iter_batched_ref(
+            || base.clone(),
+            |array| array.resize(black_box(1 << 24), false),
+            BatchSize::LargeInput,
+        );
+    });
+    group.bench_function("shrink", |b| {
+        b.iter_batched_ref(
+            || base.clone(),
+            |array| array.resize(black_box(1 << 10), false),
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
+/// Formats the array the way `Debug` did before it wrote whole bytes, one `write!` per bit.
+fn debug_per_bit(array: &BitArray) -> String {
+    let mut out = String::new();
+    for i in 0..array.bit_count() {
+        if i > 0 && i % 8 == 0 {
+            out.push(' ');
+        }
+        write!(out, "{}", u8::from(array.get(i))).unwrap();
+    }
+    out
+}
+
+fn debug_format(c: &mut Criterion) {
+    let mut group = c.benchmark_group("debug/10M");
+    group.sample_size(10);
+    let array = BitArray::from_fn(10_000_000, |i| i % 3 == 0);
+    group.bench_function("per_byte", |b| {
+        b.iter(|| format!("{:?}", black_box(&array)));
+    });
+    group.bench_function("per_bit", |b| {
+        b.iter(|| debug_per_bit(black_box(&array)));
+    });
+    group.finish();
+}
+
+criterion_group!(benches, iter_set_bits, resize, debug_format);
+criterion_main!(benches);
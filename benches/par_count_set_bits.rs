@@ -0,0 +1,25 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/bit-array-rs
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use std::hint::black_box;
+
+use bit_array_rs::BitArray;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn par_count_set_bits(c: &mut Criterion) {
+    let mut array = BitArray::from_fn(50_000_000, |i| i % 3 == 0);
+    array.set_count_tracking(false);
+
+    let mut group = c.benchmark_group("count_set_bits/50M");
+    group.bench_function("serial", |b| {
+        b.iter(|| black_box(&array).count_set_bits());
+    });
+    group.bench_function("parallel", |b| {
+        b.iter(|| black_box(&array).par_count_set_bits());
+    });
+    group.finish();
+}
+
+criterion_group!(benches, par_count_set_bits);
+criterion_main!(benches);
@@ -2,17 +2,163 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/bit-array-rs
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
-use std::ops::Index;
+use std::borrow::Cow;
+use std::ops::{BitAndAssign, BitOrAssign, BitXorAssign, Index};
 use std::vec::Vec;
 
 type BitArrayAtom = u64;
 const BIT_ARRAY_BITS_IN_ATOM: usize = 64;
 
-#[derive(Clone)]
+/// The shared values that [`BitArray::get_ref`] and indexing return references to.
+static TRUE: bool = true;
+static FALSE: bool = false;
+
+/// The index of a single bit, as opposed to an atom index or any other integer.
+///
+/// Accepted by [`BitArray::get`], [`BitArray::set`], [`BitArray::unset`] and
+/// [`BitArray::set_bit`] in place of a plain `usize`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BitIndex(pub usize);
+
+/// A value that can be used as a bit index, implemented for `usize` and [`BitIndex`].
+pub trait IntoBitIndex {
+    /// Returns the zero-based bit index.
+    fn into_bit_index(self) -> usize;
+}
+
+impl IntoBitIndex for usize {
+    fn into_bit_index(self) -> usize {
+        self
+    }
+}
+
+impl IntoBitIndex for BitIndex {
+    fn into_bit_index(self) -> usize {
+        self.0
+    }
+}
+
+/// Errors reported by the fallible `BitArray` operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitArrayError {
+    /// The two `BitArray`s of a binary operation have different `bit_count`.
+    LengthMismatch { left: usize, right: usize },
+    /// The range `start..end` is reversed or extends beyond `bit_count`.
+    RangeOutOfBounds {
+        start: usize,
+        end: usize,
+        bit_count: usize,
+    },
+}
+
+impl std::fmt::Display for BitArrayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::LengthMismatch { left, right } => write!(
+                f,
+                "BitArray length mismatch: left has {left} bits, right has {right} bits"
+            ),
+            Self::RangeOutOfBounds {
+                start,
+                end,
+                bit_count,
+            } => write!(
+                f,
+                "bit range {start}..{end} out of bounds for BitArray of {bit_count} bits"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BitArrayError {}
+
+/// Repeats a 32-bit value to fill a whole atom.
+fn repeat_u32(value: u32) -> BitArrayAtom {
+    (BitArrayAtom::from(value) << 32) | BitArrayAtom::from(value)
+}
+
+/// Returns an atom with the lowest `count` bits set.
+const fn low_bits_mask(count: usize) -> BitArrayAtom {
+    if count >= BIT_ARRAY_BITS_IN_ATOM {
+        BitArrayAtom::MAX
+    } else {
+        (1 << count) - 1
+    }
+}
+
+/// Reads `count` bits, at most one atom, starting at bit `start` of `atoms`.
+fn read_atom_bits(atoms: &[BitArrayAtom], start: usize, count: usize) -> BitArrayAtom {
+    let array_index = start / BIT_ARRAY_BITS_IN_ATOM;
+    let shift = start % BIT_ARRAY_BITS_IN_ATOM;
+
+    let mut value = atoms[array_index] >> shift;
+    if shift != 0 {
+        if let Some(&next) = atoms.get(array_index + 1) {
+            value |= next << (BIT_ARRAY_BITS_IN_ATOM - shift);
+        }
+    }
+    value & low_bits_mask(count)
+}
+
+/// Allocates the atoms for `bit_count` bits, each set to `value`.
+///
+/// Returns `None` instead of aborting if the allocation fails.
+fn allocate_atoms(bit_count: usize, value: BitArrayAtom) -> Option<Vec<BitArrayAtom>> {
+    let atom_count = bit_count.div_ceil(BIT_ARRAY_BITS_IN_ATOM);
+    let mut atoms = Vec::new();
+    atoms.try_reserve_exact(atom_count).ok()?;
+    atoms.resize(atom_count, value);
+    Some(atoms)
+}
+
+/// Returns the bit position of the n-th (zero-based) set bit in `atom`.
+const fn nth_set_bit_in_atom(mut atom: BitArrayAtom, n: usize) -> usize {
+    let mut skipped = 0;
+    while skipped < n {
+        atom &= atom - 1;
+        skipped += 1;
+    }
+    atom.trailing_zeros() as usize
+}
+
+/// Pushes the bit indices of the set bits in the atom at `atom_index`.
+fn push_atom_indices(indices: &mut Vec<usize>, atom_index: usize, mut atom: BitArrayAtom) {
+    while atom != 0 {
+        indices.push(atom_index * BIT_ARRAY_BITS_IN_ATOM + atom.trailing_zeros() as usize);
+        atom &= atom - 1;
+    }
+}
+
+/// Callback invoked with the old and new `bit_count` when a `BitArray` grows.
+///
+/// The bounds keep `BitArray` `Send`, `Sync` and unwind safe when a callback is registered.
+pub type GrowCallback =
+    Box<dyn FnMut(usize, usize) + Send + Sync + std::panic::UnwindSafe + std::panic::RefUnwindSafe>;
+
 pub struct BitArray {
     array: Vec<BitArrayAtom>,
     bit_count: usize,
+    /// Cached number of set bits, kept in sync while `count_tracking` is enabled.
+    ///
+    /// A future `Deserialize` must not trust a serialized count: it has to recompute this
+    /// from the atoms and reject payloads where `array.len() != bit_count.div_ceil(64)` with
+    /// a deserialization error, the same checks [`BitArray::from_atoms`] asserts.
     number_of_bits_set: usize,
+    count_tracking: bool,
+    on_grow: Option<GrowCallback>,
+}
+
+impl Clone for BitArray {
+    /// Clones the bits. The grow callback is not cloned, the copy starts without one.
+    fn clone(&self) -> Self {
+        Self {
+            array: self.array.clone(),
+            bit_count: self.bit_count,
+            number_of_bits_set: self.number_of_bits_set,
+            count_tracking: self.count_tracking,
+            on_grow: None,
+        }
+    }
 }
 
 impl BitArray {
@@ -23,278 +169,2655 @@ impl BitArray {
     /// * `bit_count` - The maximum number of bits in the array.
     /// # Panics
     ///
-    /// This function will panic if `bit_count` is zero.
+    /// This function will panic if `bit_count` is zero or if the storage for `bit_count`
+    /// bits can not be allocated.
     #[must_use]
     pub fn new(bit_count: usize) -> Self {
         assert_ne!(bit_count, 0, "bit_count must be greater than zero");
-        let atom_count = bit_count.div_ceil(BIT_ARRAY_BITS_IN_ATOM);
-        let array = vec![0; atom_count];
+        let array = allocate_atoms(bit_count, 0)
+            .unwrap_or_else(|| panic!("could not allocate BitArray of {bit_count} bits"));
 
         Self {
             array,
             bit_count,
             number_of_bits_set: 0,
+            count_tracking: true,
+            on_grow: None,
         }
     }
 
-    /// Resets all bits in the array.
-    pub fn reset(&mut self) {
-        self.array.fill(0);
-        self.number_of_bits_set = 0;
-    }
-
-    /// Checks if all bits are set.
+    /// Initializes a new `BitArray` with all bits set.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// * `true` if all bits in the array are set, otherwise `false`.
-    #[inline]
+    /// * `bit_count` - The maximum number of bits in the array.
+    /// # Panics
+    ///
+    /// This function will panic if `bit_count` is zero or if the storage for `bit_count`
+    /// bits can not be allocated.
     #[must_use]
-    pub const fn all_set(&self) -> bool {
-        self.bit_count == self.number_of_bits_set
+    pub fn with_all_set(bit_count: usize) -> Self {
+        assert_ne!(bit_count, 0, "bit_count must be greater than zero");
+        let array = allocate_atoms(bit_count, BitArrayAtom::MAX)
+            .unwrap_or_else(|| panic!("could not allocate BitArray of {bit_count} bits"));
+
+        let mut result = Self {
+            array,
+            bit_count,
+            number_of_bits_set: bit_count,
+            count_tracking: true,
+            on_grow: None,
+        };
+        result.mask_final_atom();
+        result
     }
 
-    /// Finds the first bit that is not set in the array.
+    /// Initializes a new `BitArray` with all bits unset, same as [`Self::new`].
     ///
-    /// # Returns
+    /// # Panics
     ///
-    /// * The index of the first unset bit, or `None` if all bits are set.
+    /// This function will panic if `bit_count` is zero.
     #[must_use]
-    pub fn first_unset_bit(&self) -> Option<usize> {
-        for (i, &atom) in self.array.iter().enumerate() {
-            if atom != u64::MAX {
-                return (0..BIT_ARRAY_BITS_IN_ATOM).find_map(|bit| {
-                    if atom & (1 << bit) == 0 {
-                        Some(i * BIT_ARRAY_BITS_IN_ATOM + bit)
-                    } else {
-                        None
-                    }
-                });
-            }
-        }
-        None
+    pub fn zeros(bit_count: usize) -> Self {
+        Self::new(bit_count)
     }
 
-    /// Finds the first bit that is set in the array.
+    /// Initializes a new `BitArray` with all bits set, same as [`Self::with_all_set`].
     ///
-    /// # Returns
+    /// # Panics
     ///
-    /// * The index of the first set bit, or `None` if no bits are set.
+    /// This function will panic if `bit_count` is zero.
     #[must_use]
-    pub fn first_set_bit(&self) -> Option<usize> {
-        for (i, &atom) in self.array.iter().enumerate() {
-            if atom != 0 {
-                return (0..BIT_ARRAY_BITS_IN_ATOM).find_map(|bit| {
-                    if atom & (1 << bit) != 0 {
-                        Some(i * BIT_ARRAY_BITS_IN_ATOM + bit)
-                    } else {
-                        None
-                    }
-                });
-            }
-        }
-        None
+    pub fn ones(bit_count: usize) -> Self {
+        Self::with_all_set(bit_count)
     }
 
-    /// Returns the number of bits that are currently set to `1`.
+    /// Initializes a new `BitArray` without panicking.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// The number of bits that are set in the `BitArray`.
-    #[inline]
-    #[must_use]
-    pub const fn count_set_bits(&self) -> usize {
-        self.number_of_bits_set
-    }
-
-    /// Returns the total number of bits in the `BitArray`.
+    /// * `bit_count` - The maximum number of bits in the array.
     ///
     /// # Returns
     ///
-    /// The total number of bits in the `BitArray`.
-    #[inline]
+    /// * `Some(BitArray)` for a valid `bit_count`, or `None` if `bit_count` is zero or the
+    ///   storage for `bit_count` bits can not be allocated.
     #[must_use]
-    pub const fn bit_count(&self) -> usize {
-        self.bit_count
+    pub fn checked_new(bit_count: usize) -> Option<Self> {
+        if bit_count == 0 {
+            return None;
+        }
+        Some(Self {
+            array: allocate_atoms(bit_count, 0)?,
+            bit_count,
+            number_of_bits_set: 0,
+            count_tracking: true,
+            on_grow: None,
+        })
     }
 
-    /// Sets the bit at the given index.
+    /// Initializes a new `BitArray` from raw atoms.
+    ///
+    /// Any bits in the final atom beyond `bit_count` are cleared.
     ///
     /// # Arguments
     ///
-    /// * `index` - The zero-based index of the bit to set.
+    /// * `bit_count` - The maximum number of bits in the array.
+    /// * `atoms` - The atoms holding the bits, least significant bit first.
     ///
     /// # Panics
     ///
-    /// This function will panic if the index is out of bounds.
-    #[inline]
-    pub fn set(&mut self, index: usize) {
-        assert!(index < self.bit_count, "Index out of bounds");
-
-        let array_index = index / BIT_ARRAY_BITS_IN_ATOM;
-        let bit_index = index % BIT_ARRAY_BITS_IN_ATOM;
-        let mask = 1 << bit_index;
-
-        if self.array[array_index] & mask == 0 {
-            self.number_of_bits_set += 1;
-        }
+    /// This function will panic if `bit_count` is zero or if the number of atoms does not
+    /// match `bit_count`.
+    #[must_use]
+    pub fn from_atoms(bit_count: usize, atoms: &[BitArrayAtom]) -> Self {
+        assert_ne!(bit_count, 0, "bit_count must be greater than zero");
+        assert_eq!(
+            atoms.len(),
+            bit_count.div_ceil(BIT_ARRAY_BITS_IN_ATOM),
+            "atom count does not match bit_count"
+        );
 
-        self.array[array_index] |= mask;
+        let mut result = Self {
+            array: atoms.to_vec(),
+            bit_count,
+            number_of_bits_set: 0,
+            count_tracking: true,
+            on_grow: None,
+        };
+        result.mask_final_atom();
+        result.recount_set_bits();
+        result
     }
 
-    /// Unsets (clears) the bit at the given index.
+    /// Initializes a new `BitArray` with the bits at the given indices set.
+    ///
+    /// Duplicate indices are allowed and are only counted once.
     ///
     /// # Arguments
     ///
-    /// * `index` - The zero-based index of the bit to clear.
+    /// * `bit_count` - The maximum number of bits in the array.
+    /// * `indices` - The zero-based indices of the bits to set.
     ///
     /// # Panics
     ///
-    /// This function will panic if the index is out of bounds.
-    #[inline]
-    pub fn unset(&mut self, index: usize) {
-        assert!(index < self.bit_count, "Index out of bounds");
-
-        let array_index = index / BIT_ARRAY_BITS_IN_ATOM;
-        let bit_index = index % BIT_ARRAY_BITS_IN_ATOM;
-        let mask = 1 << bit_index;
-
-        if self.array[array_index] & mask != 0 {
-            self.number_of_bits_set -= 1;
+    /// This function will panic if `bit_count` is zero or if any index is out of bounds.
+    #[must_use]
+    pub fn from_indices(bit_count: usize, indices: &[usize]) -> Self {
+        let mut result = Self::new(bit_count);
+        for &index in indices {
+            result.set(index);
         }
-
-        self.array[array_index] &= !mask;
+        result
     }
 
-    /// Sets or unsets the bit at the given index based on the value of `set`.
+    /// Initializes a new `BitArray` from a sparse list of `u32` set-bit indices.
+    ///
+    /// This is the inverse of [`Self::to_sparse`]. The indices do not need to be sorted, and
+    /// duplicate indices are only counted once.
     ///
     /// # Arguments
     ///
-    /// * `index` - The zero-based index of the bit to modify.
-    /// * `set` - If `true`, the bit will be set (1). If `false`, the bit will be unset (0).
+    /// * `bit_count` - The maximum number of bits in the array.
+    /// * `indices` - The zero-based indices of the bits to set.
     ///
     /// # Panics
     ///
-    /// This function will panic if the index is out of bounds.
-    pub fn set_bit(&mut self, index: usize, set: bool) {
-        assert!(index < self.bit_count, "Index out of bounds");
-
-        let array_index = index / BIT_ARRAY_BITS_IN_ATOM;
-        let bit_index = index % BIT_ARRAY_BITS_IN_ATOM;
-        let mask = 1 << bit_index;
-
-        if set {
-            if self.array[array_index] & mask == 0 {
-                self.number_of_bits_set += 1;
-            }
-            self.array[array_index] |= mask;
-        } else {
-            if self.array[array_index] & mask != 0 {
-                self.number_of_bits_set -= 1;
-            }
-            self.array[array_index] &= !mask;
+    /// This function will panic if `bit_count` is zero or if any index is out of bounds.
+    #[must_use]
+    pub fn from_sparse(bit_count: usize, indices: &[u32]) -> Self {
+        let mut result = Self::new(bit_count);
+        for &index in indices {
+            result.set(index as usize);
         }
+        result
     }
 
-    /// Returns the atom value that is located at the specified index.
+    /// Initializes a new `BitArray` by calling a function for every bit.
     ///
     /// # Arguments
     ///
-    /// * `from_index` - The index from which to start reading.
+    /// * `bit_count` - The maximum number of bits in the array.
+    /// * `f` - Called with each index from `0` to `bit_count - 1`, returns the bit value.
     ///
-    /// # Returns
+    /// # Panics
     ///
-    /// The atom value at the specified index.
+    /// This function will panic if `bit_count` is zero.
     #[must_use]
-    pub fn atom_from_index(&self, from_index: usize) -> BitArrayAtom {
-        let mut result: u64 = 0;
-
-        for i in 0..BIT_ARRAY_BITS_IN_ATOM {
-            let index = from_index + (BIT_ARRAY_BITS_IN_ATOM - 1) - i;
-            result <<= 1;
-            if index < self.bit_count {
-                result |= u64::from(self.get(index));
+    pub fn from_fn(bit_count: usize, f: impl Fn(usize) -> bool) -> Self {
+        let mut result = Self::new(bit_count);
+        for index in 0..bit_count {
+            if f(index) {
+                result.array[index / BIT_ARRAY_BITS_IN_ATOM] |=
+                    1 << (index % BIT_ARRAY_BITS_IN_ATOM);
+                result.number_of_bits_set += 1;
             }
         }
-
         result
     }
 
-    /// Returns the bit value at the specified index.
+    /// Initializes a new 32-bit `BitArray` from the bits of a `u32`.
     ///
     /// # Arguments
     ///
-    /// * `index` - The bit index to read from.
+    /// * `value` - The bits, with bit 0 in the least significant bit.
+    #[must_use]
+    pub fn from_u32(value: u32) -> Self {
+        Self::from_atoms(32, &[BitArrayAtom::from(value)])
+    }
+
+    /// Initializes a new 64-bit `BitArray` from the bits of a `u64`.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// The read bit value (0 or 1).
+    /// * `value` - The bits, with bit 0 in the least significant bit.
+    #[must_use]
+    pub fn from_u64(value: u64) -> Self {
+        Self::from_atoms(64, &[value])
+    }
+
+    /// Initializes a new `BitArray` from bytes in the layout of [`Self::to_bytes`].
+    ///
+    /// Bit 0 is read from the least significant bit of byte 0. Unused bits in the last byte
+    /// are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `bit_count` - The maximum number of bits in the array.
+    /// * `bytes` - The packed bits, `bit_count.div_ceil(8)` bytes.
     ///
     /// # Panics
     ///
-    /// This function will panic if the index is out of bounds.
+    /// This function will panic if `bit_count` is zero or if the number of bytes does not
+    /// match `bit_count`.
     #[must_use]
-    pub fn get(&self, index: usize) -> bool {
-        assert!(index < self.bit_count, "Index out of bounds");
-
-        let array_index = index / BIT_ARRAY_BITS_IN_ATOM;
-        let bit_index = index % BIT_ARRAY_BITS_IN_ATOM;
-
-        ((self.array[array_index] >> bit_index) & 0x1) != 0
+    pub fn from_bytes(bit_count: usize, bytes: &[u8]) -> Self {
+        assert_eq!(
+            bytes.len(),
+            bit_count.div_ceil(8),
+            "byte count does not match bit_count"
+        );
+        let atoms: Vec<BitArrayAtom> = bytes
+            .chunks(BIT_ARRAY_BITS_IN_ATOM / 8)
+            .map(|chunk| {
+                let mut atom_bytes = [0; BIT_ARRAY_BITS_IN_ATOM / 8];
+                atom_bytes[..chunk.len()].copy_from_slice(chunk);
+                BitArrayAtom::from_le_bytes(atom_bytes)
+            })
+            .collect();
+        Self::from_atoms(bit_count, &atoms)
     }
-}
 
-impl Index<usize> for BitArray {
-    type Output = bool;
-    /// Provides indexed access to individual bits in the `BitArray`.
+    /// Initializes a new `BitArray` from bytes in the layout of [`Self::to_be_bytes`].
+    ///
+    /// Bit 0 is read from the most significant bit of byte 0. Unused bits in the last byte
+    /// are ignored.
     ///
     /// # Arguments
     ///
-    /// * `index` - The zero-based index of the bit to access.
+    /// * `bit_count` - The maximum number of bits in the array.
+    /// * `bytes` - The packed bits, `bit_count.div_ceil(8)` bytes.
     ///
-    /// # Returns
+    /// # Panics
     ///
-    /// A reference to a boolean value (`true` or `false`) representing the state of the bit
-    /// at the specified index.
+    /// This function will panic if `bit_count` is zero or if the number of bytes does not
+    /// match `bit_count`.
+    #[must_use]
+    pub fn from_be_bytes(bit_count: usize, bytes: &[u8]) -> Self {
+        let le_bytes: Vec<u8> = bytes.iter().map(|byte| byte.reverse_bits()).collect();
+        Self::from_bytes(bit_count, &le_bytes)
+    }
+
+    /// Initializes a new `BitArray` from a `bitvec` bit vector.
     ///
-    /// # Panics
+    /// The bit vector uses the `Lsb0` ordering, so bit `i` of `bits` becomes bit `i` of the
+    /// `BitArray`, matching the least significant bit first layout of the atoms.
     ///
-    /// This function will panic if the `index` is out of bounds.
+    /// # Arguments
     ///
-    /// # Example
+    /// * `bits` - The bits to copy, its length becomes the `bit_count`.
     ///
-    /// ```
-    /// use bit_array_rs::BitArray;
-    /// let mut bit_array = BitArray::new(16);
-    /// bit_array.set(3);
-    /// assert_eq!(bit_array[3], true);
-    /// assert_eq!(bit_array[0], false);
-    /// ```
-    fn index(&self, index: usize) -> &Self::Output {
-        if self.get(index) {
-            &true
-        } else {
-            &false
-        }
+    /// # Panics
+    ///
+    /// This function will panic if `bits` is empty.
+    #[cfg(feature = "bitvec")]
+    #[must_use]
+    pub fn from_bitvec(bits: &bitvec::vec::BitVec) -> Self {
+        use bitvec::field::BitField;
+
+        let atoms: Vec<BitArrayAtom> = bits
+            .chunks(BIT_ARRAY_BITS_IN_ATOM)
+            .map(BitField::load_le)
+            .collect();
+        Self::from_atoms(bits.len(), &atoms)
     }
-}
 
-impl std::fmt::Debug for BitArray {
-    /// Formats the `BitArray` as a binary string with groups of 8 bits separated by a space.
+    /// Initializes a new `BitArray` from runs of equal values.
     ///
     /// # Arguments
     ///
-    /// * `f` - The formatter used to output the debug string.
+    /// * `pairs` - `(value, length)` pairs, as produced by [`Self::to_rle`]. Runs of length
+    ///   zero are allowed and contribute nothing.
     ///
-    /// # Returns
+    /// # Panics
     ///
-    /// A `Result` indicating whether the formatting was successful.
+    /// This function will panic if the run lengths add up to zero.
+    #[must_use]
+    pub fn from_rle(pairs: &[(bool, usize)]) -> Self {
+        let bit_count = pairs.iter().map(|&(_, length)| length).sum();
+        let mut result = Self::new(bit_count);
+
+        let mut start = 0;
+        for &(value, length) in pairs {
+            if value {
+                result.write_range(start, start + length, true);
+            }
+            start += length;
+        }
+        result
+    }
+
+    /// Resets all bits in the array.
     ///
-    /// # Example
+    /// The `bit_count` and the allocated capacity are kept, so the array can be reused
+    /// without reallocating.
+    pub fn reset(&mut self) {
+        self.array.fill(0);
+        self.number_of_bits_set = 0;
+    }
+
+    /// Sets all bits in the array.
     ///
-    /// ```
-    /// use bit_array_rs::BitArray;
-    /// let mut bit_array = BitArray::new(16);
+    /// The padding bits beyond `bit_count` in the final atom are left cleared.
+    pub fn set_all(&mut self) {
+        self.array.fill(BitArrayAtom::MAX);
+        self.mask_final_atom();
+        self.number_of_bits_set = self.bit_count;
+    }
+
+    /// Overwrites all bits with a repeating 32-bit pattern.
+    ///
+    /// Bit `i` of the array is set to bit `i % 32` of `pattern`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern` - The pattern to repeat.
+    pub fn fill_pattern(&mut self, pattern: u32) {
+        self.array.fill(repeat_u32(pattern));
+        self.mask_final_atom();
+        self.recount_set_bits();
+    }
+
+    /// Clears all bits stored in a single backing atom.
+    ///
+    /// # Arguments
+    ///
+    /// * `atom_index` - The zero-based index of the atom to clear.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `atom_index` is not less than [`Self::atom_len`].
+    pub fn reset_atom(&mut self, atom_index: usize) {
+        assert!(atom_index < self.atom_len(), "Atom index out of bounds");
+
+        let previous = self.array[atom_index] & self.atom_mask(atom_index);
+        self.sub_from_count(previous.count_ones() as usize);
+        self.array[atom_index] = 0;
+    }
+
+    /// Overwrites all bits stored in a single backing atom.
+    ///
+    /// Bits beyond `bit_count` in the final atom are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `atom_index` - The zero-based index of the atom to write.
+    /// * `value` - The new bits of the atom, least significant bit first.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `atom_index` is not less than [`Self::atom_len`].
+    pub fn set_atom(&mut self, atom_index: usize, value: BitArrayAtom) {
+        assert!(atom_index < self.atom_len(), "Atom index out of bounds");
+
+        let mask = self.atom_mask(atom_index);
+        let previous = self.array[atom_index] & mask;
+        let value = value & mask;
+        self.sub_from_count(previous.count_ones() as usize);
+        self.add_to_count(value.count_ones() as usize);
+        self.array[atom_index] = value;
+    }
+
+    /// Checks if all bits are set.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if all bits in the array are set, otherwise `false`.
+    #[inline]
+    #[must_use]
+    pub const fn all_set(&self) -> bool {
+        self.bit_count == self.count_set_bits()
+    }
+
+    /// Checks if all bits are set, same as [`Self::all_set`].
+    #[inline]
+    #[must_use]
+    pub const fn is_full(&self) -> bool {
+        self.all_set()
+    }
+
+    /// Checks if all bits are set by inspecting the atoms instead of the cached count.
+    ///
+    /// Slower than [`Self::all_set`], but still correct if the number of set bits has gone
+    /// out of sync, e.g. after modifying [`Self::atoms_mut`] without a recount.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if every valid bit in the atoms is set, otherwise `false`.
+    #[must_use]
+    pub fn all_set_verified(&self) -> bool {
+        self.array.iter().enumerate().all(|(atom_index, &atom)| {
+            let mask = self.atom_mask(atom_index);
+            atom & mask == mask
+        })
+    }
+
+    /// Checks the internal invariants of the array.
+    ///
+    /// Useful as an assertion after modifying the atoms through [`Self::atoms_mut`].
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the number of atoms matches `bit_count`, the padding bits beyond
+    ///   `bit_count` are cleared, and the cached number of set bits matches the atoms (or
+    ///   count tracking is disabled), otherwise `false`.
+    #[must_use]
+    pub const fn is_canonical(&self) -> bool {
+        let padding_clear = match self.array.as_slice().last() {
+            Some(&last) => last & !self.final_atom_mask() == 0,
+            None => false,
+        };
+        self.array.len() == self.bit_count.div_ceil(BIT_ARRAY_BITS_IN_ATOM)
+            && padding_clear
+            && self.count_set_bits() == self.count_atom_ones()
+    }
+
+    /// Checks if no bits are set.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if no bits in the array are set, otherwise `false`.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.count_set_bits() == 0
+    }
+
+    /// Checks if exactly one bit is set.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if exactly one bit in the array is set, otherwise `false`.
+    #[inline]
+    #[must_use]
+    pub const fn has_single_bit(&self) -> bool {
+        self.count_set_bits() == 1
+    }
+
+    /// Finds the first bit that is not set in the array.
+    ///
+    /// # Returns
+    ///
+    /// * The index of the first unset bit, or `None` if all bits are set.
+    #[must_use]
+    pub fn first_unset_bit(&self) -> Option<usize> {
+        for (i, &atom) in self.array.iter().enumerate() {
+            if atom != u64::MAX {
+                let index = i * BIT_ARRAY_BITS_IN_ATOM + (!atom).trailing_zeros() as usize;
+                // The padding bits of the final atom are always zero, so they must not be reported
+                return (index < self.bit_count).then_some(index);
+            }
+        }
+        None
+    }
+
+    /// Finds the first bit that is set in the array.
+    ///
+    /// # Returns
+    ///
+    /// * The index of the first set bit, or `None` if no bits are set.
+    #[must_use]
+    pub fn first_set_bit(&self) -> Option<usize> {
+        for (i, &atom) in self.array.iter().enumerate() {
+            if atom != 0 {
+                return Some(i * BIT_ARRAY_BITS_IN_ATOM + atom.trailing_zeros() as usize);
+            }
+        }
+        None
+    }
+
+    /// Finds the next position after `index` where the bit value changes.
+    ///
+    /// Calling this repeatedly walks the runs of equal bits without building the RLE.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The zero-based index of the bit to compare against.
+    ///
+    /// # Returns
+    ///
+    /// * The smallest index greater than `index` whose bit differs from the bit at `index`,
+    ///   or `None` if all following bits are equal to it.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    #[must_use]
+    pub fn next_change_from(&self, index: usize) -> Option<usize> {
+        let value = self.get(index);
+        self.find_from(index + 1, !value)
+    }
+
+    /// Returns the index of the only set bit.
+    ///
+    /// # Returns
+    ///
+    /// * The index of the set bit if exactly one bit is set, otherwise `None`.
+    #[must_use]
+    pub fn only_bit(&self) -> Option<usize> {
+        if self.has_single_bit() {
+            self.first_set_bit()
+        } else {
+            None
+        }
+    }
+
+    /// Finds the n-th (zero-based) bit that is not set.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of unset bits to skip.
+    ///
+    /// # Returns
+    ///
+    /// * The index of the n-th unset bit, or `None` if there are not more than `n` unset bits.
+    #[must_use]
+    pub fn nth_unset_bit(&self, n: usize) -> Option<usize> {
+        let mut remaining = n;
+        for (atom_index, &atom) in self.array.iter().enumerate() {
+            let unset = !atom & self.atom_mask(atom_index);
+            let zeros = unset.count_ones() as usize;
+            if remaining < zeros {
+                return Some(
+                    atom_index * BIT_ARRAY_BITS_IN_ATOM + nth_set_bit_in_atom(unset, remaining),
+                );
+            }
+            remaining -= zeros;
+        }
+        None
+    }
+
+    /// Finds the first bit that is set and clears it.
+    ///
+    /// # Returns
+    ///
+    /// * The index of the cleared bit, or `None` if no bits are set.
+    pub fn clear_first_set(&mut self) -> Option<usize> {
+        let index = self.first_set_bit()?;
+        self.array[index / BIT_ARRAY_BITS_IN_ATOM] &= !(1 << (index % BIT_ARRAY_BITS_IN_ATOM));
+        self.sub_from_count(1);
+        Some(index)
+    }
+
+    /// Returns the first set bit together with a copy of the array with that bit cleared.
+    ///
+    /// This is the non-mutating counterpart of [`Self::clear_first_set`].
+    ///
+    /// # Returns
+    ///
+    /// * The index of the first set bit and the copy, or `None` if no bits are set.
+    #[must_use]
+    pub fn split_first_set(&self) -> Option<(usize, Self)> {
+        let mut rest = self.clone();
+        let index = rest.clear_first_set()?;
+        Some((index, rest))
+    }
+
+    /// Returns the number of bits that are currently set to `1`.
+    ///
+    /// This reads the cached count in O(1). While count tracking is disabled with
+    /// [`Self::set_count_tracking`], it recounts the atoms on every call, which is O(atoms).
+    ///
+    /// # Returns
+    ///
+    /// The number of bits that are set in the `BitArray`.
+    #[inline]
+    #[must_use]
+    pub const fn count_set_bits(&self) -> usize {
+        if self.count_tracking {
+            self.number_of_bits_set
+        } else {
+            self.count_atom_ones()
+        }
+    }
+
+    /// Enables or disables maintaining the number of set bits on every modification.
+    ///
+    /// With tracking disabled, [`Self::set`], [`Self::unset`] and the other mutators skip
+    /// the count bookkeeping, and every query that needs the count, such as
+    /// [`Self::count_set_bits`] or [`Self::all_set`], recounts the atoms instead. This
+    /// suits heavy bit or atom manipulation followed by few count queries. Enabling
+    /// tracking again recounts the atoms once and resumes the cached count.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - `true` to maintain the cached count, `false` to recount on demand.
+    pub const fn set_count_tracking(&mut self, enabled: bool) {
+        if enabled && !self.count_tracking {
+            self.recount_set_bits();
+        }
+        self.count_tracking = enabled;
+    }
+
+    /// Returns the number of set bits at the positions that are set in `mask`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - The `BitArray` selecting which positions to count.
+    ///
+    /// # Returns
+    ///
+    /// The number of bits set in both `self` and `mask`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the two arrays have different `bit_count`.
+    #[must_use]
+    pub fn count_set_bits_masked(&self, mask: &Self) -> usize {
+        self.assert_same_len(mask);
+        self.array
+            .iter()
+            .zip(&mask.array)
+            .map(|(&atom, &mask_atom)| (atom & mask_atom).count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns the number of bits that are currently set to `0`.
+    ///
+    /// # Returns
+    ///
+    /// The number of bits that are not set in the `BitArray`.
+    #[inline]
+    #[must_use]
+    pub const fn count_unset_bits(&self) -> usize {
+        self.bit_count - self.count_set_bits()
+    }
+
+    /// Returns the number of unset bits before a position.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The end of the range `0..index` to count in.
+    ///
+    /// # Returns
+    ///
+    /// The number of bits in `0..index` that are not set.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `index > bit_count`.
+    #[must_use]
+    pub fn rank0(&self, index: usize) -> usize {
+        assert!(index <= self.bit_count, "Index out of bounds");
+
+        let full_atoms = index / BIT_ARRAY_BITS_IN_ATOM;
+        let mut zeros: usize = self.array[..full_atoms]
+            .iter()
+            .map(|atom| atom.count_zeros() as usize)
+            .sum();
+
+        let remaining_bits = index % BIT_ARRAY_BITS_IN_ATOM;
+        if remaining_bits != 0 {
+            let atom = self.array[full_atoms];
+            zeros += (!atom & low_bits_mask(remaining_bits)).count_ones() as usize;
+        }
+        zeros
+    }
+
+    /// Returns the total number of bits in the `BitArray`.
+    ///
+    /// # Returns
+    ///
+    /// The total number of bits in the `BitArray`.
+    #[inline]
+    #[must_use]
+    pub const fn bit_count(&self) -> usize {
+        self.bit_count
+    }
+
+    /// Returns the number of atoms backing the `BitArray`.
+    ///
+    /// # Returns
+    ///
+    /// The number of atoms, each holding up to 64 bits.
+    #[inline]
+    #[must_use]
+    pub const fn atom_len(&self) -> usize {
+        self.array.len()
+    }
+
+    /// Returns the number of bits the array can hold without reallocating.
+    ///
+    /// The backing storage is allocated in whole atoms, so this is a multiple of 64 and is
+    /// never less than [`Self::bit_count`].
+    ///
+    /// # Returns
+    ///
+    /// The allocated capacity of the backing storage, in bits.
+    #[must_use]
+    pub const fn capacity_bits(&self) -> usize {
+        self.array.capacity() * BIT_ARRAY_BITS_IN_ATOM
+    }
+
+    /// Returns the number of set bits in a single backing atom.
+    ///
+    /// # Arguments
+    ///
+    /// * `atom_index` - The zero-based index of the atom.
+    ///
+    /// # Returns
+    ///
+    /// The number of set bits in the atom, not counting bits beyond `bit_count`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `atom_index` is not less than [`Self::atom_len`].
+    #[must_use]
+    pub fn atom_count_ones(&self, atom_index: usize) -> u32 {
+        assert!(atom_index < self.atom_len(), "Atom index out of bounds");
+        (self.array[atom_index] & self.atom_mask(atom_index)).count_ones()
+    }
+
+    /// Returns the number of set bits in a range of backing atoms.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_atom` - The index of the first atom to count.
+    /// * `end_atom` - The index one past the last atom to count.
+    ///
+    /// # Returns
+    ///
+    /// The number of set bits in atoms `start_atom..end_atom`, not counting bits beyond
+    /// `bit_count`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `start_atom > end_atom` or `end_atom > atom_len()`.
+    #[must_use]
+    pub fn count_set_bits_in_atoms(&self, start_atom: usize, end_atom: usize) -> usize {
+        assert!(
+            start_atom <= end_atom && end_atom <= self.atom_len(),
+            "Atom range out of bounds"
+        );
+        (start_atom..end_atom)
+            .map(|atom_index| self.atom_count_ones(atom_index) as usize)
+            .sum()
+    }
+
+    /// Returns the number of bits that are set, counting the atoms in parallel.
+    ///
+    /// The atoms are split into chunks that are popcounted on the rayon thread pool, so
+    /// this does not depend on count tracking and is meant for very large arrays.
+    ///
+    /// # Returns
+    ///
+    /// The number of set bits, equal to [`Self::count_set_bits`].
+    #[cfg(feature = "rayon")]
+    #[must_use]
+    pub fn par_count_set_bits(&self) -> usize {
+        use rayon::prelude::*;
+
+        const ATOMS_PER_CHUNK: usize = 4096;
+        let Some((last, full_atoms)) = self.array.split_last() else {
+            return 0;
+        };
+        let full_count: usize = full_atoms
+            .par_chunks(ATOMS_PER_CHUNK)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|atom| atom.count_ones() as usize)
+                    .sum::<usize>()
+            })
+            .sum();
+        full_count + (last & self.final_atom_mask()).count_ones() as usize
+    }
+
+    /// Returns the backing atoms.
+    ///
+    /// # Returns
+    ///
+    /// The atoms holding the bits, least significant bit first.
+    #[inline]
+    #[must_use]
+    pub fn atoms(&self) -> &[BitArrayAtom] {
+        &self.array
+    }
+
+    /// Returns the backing atoms as bytes, without copying.
+    ///
+    /// Each atom is stored in native byte order, so the bytes only match across platforms
+    /// with the same endianness; use [`Self::to_bytes`] for a portable encoding. The
+    /// padding bits beyond `bit_count` are zero, unless they were set through
+    /// [`Self::atoms_mut`], so equal arrays yield equal bytes.
+    ///
+    /// # Returns
+    ///
+    /// The bytes of all backing atoms, eight per atom.
+    #[must_use]
+    pub const fn as_byte_slice(&self) -> &[u8] {
+        // SAFETY: the pointer and length cover exactly the initialized atoms of the Vec,
+        // `u8` has no alignment requirement and every bit pattern is a valid `u8`, and the
+        // returned slice borrows `self`, so the atoms can not be modified or freed while it
+        // is alive.
+        unsafe {
+            std::slice::from_raw_parts(
+                self.array.as_ptr().cast::<u8>(),
+                std::mem::size_of_val(self.array.as_slice()),
+            )
+        }
+    }
+
+    /// Returns the backing atoms with the bits beyond `bit_count` guaranteed to be cleared.
+    ///
+    /// # Returns
+    ///
+    /// The backing atoms, borrowed if the padding bits of the final atom are already clear,
+    /// otherwise a copy with them cleared.
+    #[must_use]
+    pub fn atoms_masked(&self) -> Cow<'_, [BitArrayAtom]> {
+        let mask = self.final_atom_mask();
+        match self.array.last() {
+            Some(&last) if last & !mask != 0 => {
+                let mut atoms = self.array.clone();
+                if let Some(last) = atoms.last_mut() {
+                    *last &= mask;
+                }
+                Cow::Owned(atoms)
+            }
+            _ => Cow::Borrowed(&self.array),
+        }
+    }
+
+    /// Folds all backing atoms into an accumulator, with the final atom masked.
+    ///
+    /// The bits beyond `bit_count` are always cleared in the atom passed to `f`, even if
+    /// they were set through [`Self::atoms_mut`].
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - The initial value of the accumulator.
+    /// * `f` - Combines the accumulator with the next atom, least significant bit first.
+    ///
+    /// # Returns
+    ///
+    /// The final value of the accumulator.
+    pub fn fold_atoms<B>(&self, init: B, mut f: impl FnMut(B, BitArrayAtom) -> B) -> B {
+        self.array
+            .iter()
+            .enumerate()
+            .fold(init, |acc, (atom_index, &atom)| {
+                f(acc, atom & self.atom_mask(atom_index))
+            })
+    }
+
+    /// Returns the backing atoms for direct modification.
+    ///
+    /// The number of set bits is not updated, so [`Self::recount_set_bits`] must be called
+    /// afterwards. The bits beyond `bit_count` in the final atom must be left cleared, or be
+    /// cleared by calling [`Self::normalize`] instead.
+    ///
+    /// # Returns
+    ///
+    /// The atoms holding the bits, least significant bit first.
+    #[inline]
+    #[must_use]
+    pub fn atoms_mut(&mut self) -> &mut [BitArrayAtom] {
+        &mut self.array
+    }
+
+    /// Returns the fraction of bits that are set.
+    ///
+    /// # Returns
+    ///
+    /// A value between `0.0` and `1.0`. Defined as `0.0` for a zero-length array.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn density(&self) -> f64 {
+        if self.bit_count == 0 {
+            return 0.0;
+        }
+        self.count_set_bits() as f64 / self.bit_count as f64
+    }
+
+    /// Sets the bit at the given index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The zero-based index of the bit to set, a `usize` or a [`BitIndex`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    #[inline]
+    #[track_caller]
+    pub fn set(&mut self, index: impl IntoBitIndex) {
+        let index = index.into_bit_index();
+        self.assert_in_bounds(index);
+
+        let array_index = index / BIT_ARRAY_BITS_IN_ATOM;
+        let bit_index = index % BIT_ARRAY_BITS_IN_ATOM;
+        let mask = 1 << bit_index;
+
+        if self.count_tracking && self.array[array_index] & mask == 0 {
+            self.number_of_bits_set += 1;
+        }
+
+        self.array[array_index] |= mask;
+    }
+
+    /// Unsets (clears) the bit at the given index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The zero-based index of the bit to clear, a `usize` or a [`BitIndex`].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    #[inline]
+    #[track_caller]
+    pub fn unset(&mut self, index: impl IntoBitIndex) {
+        let index = index.into_bit_index();
+        self.assert_in_bounds(index);
+
+        let array_index = index / BIT_ARRAY_BITS_IN_ATOM;
+        let bit_index = index % BIT_ARRAY_BITS_IN_ATOM;
+        let mask = 1 << bit_index;
+
+        if self.count_tracking && self.array[array_index] & mask != 0 {
+            debug_assert!(
+                self.number_of_bits_set > 0,
+                "number of set bits is out of sync, call recount_set_bits()"
+            );
+            self.number_of_bits_set -= 1;
+        }
+
+        self.array[array_index] &= !mask;
+    }
+
+    /// Sets or unsets the bit at the given index based on the value of `set`.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The zero-based index of the bit to modify, a `usize` or a [`BitIndex`].
+    /// * `set` - If `true`, the bit will be set (1). If `false`, the bit will be unset (0).
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    #[track_caller]
+    pub fn set_bit(&mut self, index: impl IntoBitIndex, set: bool) {
+        let index = index.into_bit_index();
+        self.assert_in_bounds(index);
+
+        let mask = 1 << (index % BIT_ARRAY_BITS_IN_ATOM);
+        let atom = &mut self.array[index / BIT_ARRAY_BITS_IN_ATOM];
+
+        let was_set = *atom & mask != 0;
+        if self.count_tracking && set != was_set {
+            if set {
+                self.number_of_bits_set += 1;
+            } else {
+                debug_assert!(
+                    self.number_of_bits_set > 0,
+                    "number of set bits is out of sync, call recount_set_bits()"
+                );
+                self.number_of_bits_set -= 1;
+            }
+        }
+
+        *atom = if set { *atom | mask } else { *atom & !mask };
+    }
+
+    /// Sets the bit at the given index to `value` and returns its previous value.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The zero-based index of the bit to modify.
+    /// * `value` - The new value of the bit.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the bit was set before the call.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    pub fn replace(&mut self, index: usize, value: bool) -> bool {
+        let previous = self.get(index);
+        self.set_bit(index, value);
+        previous
+    }
+
+    /// Returns the atom value that is located at the specified index.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_index` - The index from which to start reading.
+    ///
+    /// # Returns
+    ///
+    /// The atom value at the specified index.
+    #[must_use]
+    pub fn atom_from_index(&self, from_index: usize) -> BitArrayAtom {
+        let mut result: u64 = 0;
+
+        for i in 0..BIT_ARRAY_BITS_IN_ATOM {
+            let index = from_index + (BIT_ARRAY_BITS_IN_ATOM - 1) - i;
+            result <<= 1;
+            if index < self.bit_count {
+                result |= u64::from(self.get(index));
+            }
+        }
+
+        result
+    }
+
+    /// Returns the bit value at the specified index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The bit index to read from, a `usize` or a [`BitIndex`].
+    ///
+    /// # Returns
+    ///
+    /// The read bit value (0 or 1).
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    #[must_use]
+    #[track_caller]
+    pub fn get(&self, index: impl IntoBitIndex) -> bool {
+        let index = index.into_bit_index();
+        self.assert_in_bounds(index);
+
+        let array_index = index / BIT_ARRAY_BITS_IN_ATOM;
+        let bit_index = index % BIT_ARRAY_BITS_IN_ATOM;
+
+        ((self.array[array_index] >> bit_index) & 0x1) != 0
+    }
+
+    /// Returns the bit value at the index given by a flag, e.g. a C-like enum.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag` - A value that converts into the bit index.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    #[must_use]
+    pub fn get_enum<E: Into<usize> + Copy>(&self, flag: E) -> bool {
+        self.get(flag.into())
+    }
+
+    /// Sets the bit at the index given by a flag, e.g. a C-like enum.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag` - A value that converts into the bit index.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    pub fn set_enum<E: Into<usize> + Copy>(&mut self, flag: E) {
+        self.set(flag.into());
+    }
+
+    /// Returns a reference to the bit value at the specified index.
+    ///
+    /// The reference does not point into the `BitArray`; it is one of two shared statics,
+    /// so the same value always yields the same reference, for every `BitArray`. Use
+    /// [`Self::set_bit`] to modify a bit.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The bit index to read from.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    #[must_use]
+    pub fn get_ref(&self, index: usize) -> &'static bool {
+        if self.get(index) {
+            &TRUE
+        } else {
+            &FALSE
+        }
+    }
+
+    /// Returns an iterator over the indices of the set bits.
+    ///
+    /// # Returns
+    ///
+    /// A [`SetBitsIter`] that yields the indices in ascending order, or in descending order
+    /// when iterated from the back.
+    #[must_use]
+    pub const fn iter_set_bits(&self) -> SetBitsIter<'_> {
+        SetBitsIter {
+            atoms: self.array.as_slice(),
+            middle_start: 0,
+            front_atom: 0,
+            front_base: 0,
+            back_atom: 0,
+            back_base: 0,
+        }
+    }
+
+    /// Collects the indices of the set bits.
+    ///
+    /// # Returns
+    ///
+    /// The indices of all set bits in ascending order.
+    #[must_use]
+    pub fn collect_set_bits(&self) -> Vec<usize> {
+        self.iter_set_bits().collect()
+    }
+
+    /// Converts the array to a sparse list of `u32` set-bit indices.
+    ///
+    /// For very sparse arrays this uses less memory than the dense atoms. Use
+    /// [`Self::from_sparse`] to convert back.
+    ///
+    /// # Returns
+    ///
+    /// The indices of all set bits in ascending order.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if a set bit index does not fit in a `u32`.
+    #[must_use]
+    pub fn to_sparse(&self) -> Vec<u32> {
+        let mut indices = Vec::with_capacity(self.count_set_bits());
+        indices.extend(self.iter_set_bits().map(|index| {
+            u32::try_from(index)
+                .unwrap_or_else(|_| panic!("bit index {index} does not fit in a u32"))
+        }));
+        indices
+    }
+
+    /// Writes the indices of the set bits to a writer, without collecting them first.
+    ///
+    /// Each index is written as a little-endian `u32`, in ascending order. Nothing else is
+    /// written, so the number of indices is the number of bytes divided by four.
+    ///
+    /// # Arguments
+    ///
+    /// * `w` - The writer that receives the indices.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from the writer, or an error of kind `InvalidInput` if a set bit
+    /// index does not fit in a `u32`.
+    pub fn write_set_indices<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        for index in self.iter_set_bits() {
+            let index = u32::try_from(index).map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("bit index {index} does not fit in a u32"),
+                )
+            })?;
+            w.write_all(&index.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Writes the bits as a binary string to a writer, without building a `String`.
+    ///
+    /// # Arguments
+    ///
+    /// * `w` - The writer that receives the text.
+    /// * `grouped` - If `true`, writes the grouped `Debug` form, otherwise the `Display` form.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from the writer.
+    pub fn write_binary<W: std::io::Write>(&self, w: &mut W, grouped: bool) -> std::io::Result<()> {
+        if grouped {
+            write!(w, "{self:?}")
+        } else {
+            write!(w, "{self}")
+        }
+    }
+
+    /// Sets the bits whose indices are read from a reader.
+    ///
+    /// Reads little-endian `u32` indices until the end of the reader, in the format written
+    /// by [`Self::write_set_indices`]. Bits that are already set are left set. If an error
+    /// is returned, the bits read before the error remain set.
+    ///
+    /// # Arguments
+    ///
+    /// * `r` - The reader that provides the indices.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from the reader, an error of kind `InvalidData` if an index is out
+    /// of bounds, or an error of kind `UnexpectedEof` if the input ends inside an index.
+    pub fn set_indices_from_reader<R: std::io::Read>(&mut self, r: &mut R) -> std::io::Result<()> {
+        let mut buf = [0u8; 4];
+        loop {
+            let mut filled = 0;
+            while filled < buf.len() {
+                match r.read(&mut buf[filled..]) {
+                    Ok(0) => break,
+                    Ok(n) => filled += n,
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(err) => return Err(err),
+                }
+            }
+
+            if filled == 0 {
+                return Ok(());
+            }
+            if filled < buf.len() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    "input ends inside a bit index",
+                ));
+            }
+
+            let index = u32::from_le_bytes(buf) as usize;
+            if index >= self.bit_count {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "bit index {index} out of bounds for BitArray of {} bits",
+                        self.bit_count
+                    ),
+                ));
+            }
+            self.set(index);
+        }
+    }
+
+    /// Returns a borrowed read-only view of the bits in `start..end`.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first bit in the view.
+    /// * `end` - The index one past the last bit in the view.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `start > end` or `end > bit_count`.
+    #[must_use]
+    pub fn view(&self, start: usize, end: usize) -> BitSlice<'_> {
+        assert!(start <= end && end <= self.bit_count, "Range out of bounds");
+        BitSlice {
+            atoms: &self.array,
+            start,
+            len: end - start,
+        }
+    }
+
+    /// Returns a copy of the bits in `start..end` as a new `BitArray`.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first bit to copy.
+    /// * `end` - The index one past the last bit to copy.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `start >= end` or `end > bit_count`.
+    #[must_use]
+    pub fn slice(&self, start: usize, end: usize) -> Self {
+        assert!(start < end && end <= self.bit_count, "Range out of bounds");
+        let mut result = Self::new(end - start);
+        result.copy_bits_from(self, start, 0, end - start);
+        result
+    }
+
+    /// Returns an iterator over the bits, starting from index 0.
+    ///
+    /// # Returns
+    ///
+    /// An [`Iter`] yielding the bit values from index `0` up to `bit_count - 1`.
+    #[must_use]
+    pub const fn iter(&self) -> Iter<'_> {
+        Iter {
+            bit_array: self,
+            front: 0,
+            back: self.bit_count,
+        }
+    }
+
+    /// Returns an iterator over the bits, starting from index `start`.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first bit to yield, `bit_count` for an empty iterator.
+    ///
+    /// # Returns
+    ///
+    /// An [`Iter`] yielding the bit values from index `start` up to `bit_count - 1`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `start` is greater than `bit_count`.
+    #[must_use]
+    pub fn iter_from(&self, start: usize) -> Iter<'_> {
+        assert!(
+            start <= self.bit_count,
+            "start {start} out of bounds for BitArray of {} bits",
+            self.bit_count
+        );
+        Iter {
+            bit_array: self,
+            front: start,
+            back: self.bit_count,
+        }
+    }
+
+    /// Returns an iterator over the bits, starting from the highest index.
+    ///
+    /// # Returns
+    ///
+    /// An iterator yielding the bit values from index `bit_count - 1` down to `0`.
+    pub fn iter_rev(&self) -> impl Iterator<Item = bool> + '_ {
+        self.iter().rev()
+    }
+
+    /// Returns the bits as a `u32`, with bit 0 in the least significant bit.
+    ///
+    /// # Returns
+    ///
+    /// * The bits if `bit_count` is at most 32, otherwise `None`.
+    #[must_use]
+    pub fn as_u32(&self) -> Option<u32> {
+        if self.bit_count > 32 {
+            return None;
+        }
+        u32::try_from(self.array[0] & self.final_atom_mask()).ok()
+    }
+
+    /// Returns the bits as a `u64`, with bit 0 in the least significant bit.
+    ///
+    /// # Returns
+    ///
+    /// * The bits if `bit_count` is at most 64, otherwise `None`.
+    #[must_use]
+    pub fn as_u64(&self) -> Option<u64> {
+        if self.bit_count > 64 {
+            return None;
+        }
+        Some(self.array[0] & self.final_atom_mask())
+    }
+
+    /// Returns the bits packed into bytes.
+    ///
+    /// Bit 0 is stored in the least significant bit of byte 0, bit 8 in the least significant
+    /// bit of byte 1, and so on. Unused bits in the last byte are zero.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `bit_count.div_ceil(8)` bytes.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self
+            .array
+            .iter()
+            .flat_map(|atom| atom.to_le_bytes())
+            .collect();
+        bytes.truncate(self.bit_count.div_ceil(8));
+        bytes
+    }
+
+    /// Returns the bits packed into bytes, most significant bit first.
+    ///
+    /// Bit 0 is stored in the most significant bit of byte 0, bit 7 in the least significant
+    /// bit of byte 0, bit 8 in the most significant bit of byte 1, and so on. The bytes are in
+    /// the same order as [`Self::to_bytes`], but the bits within each byte are reversed.
+    /// Unused bits in the last byte are zero.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `bit_count.div_ceil(8)` bytes.
+    #[must_use]
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        self.to_bytes().into_iter().map(u8::reverse_bits).collect()
+    }
+
+    /// Returns the bits as a `bitvec` bit vector.
+    ///
+    /// The bit vector uses the `Lsb0` ordering, so bit `i` of the `BitArray` becomes bit `i`
+    /// of the result.
+    ///
+    /// # Returns
+    ///
+    /// A `BitVec` of `bit_count` bits.
+    #[cfg(feature = "bitvec")]
+    #[must_use]
+    pub fn to_bitvec(&self) -> bitvec::vec::BitVec {
+        use bitvec::view::BitView;
+
+        let mut bits = bitvec::vec::BitVec::with_capacity(self.bit_count);
+        for atom in &self.array {
+            bits.extend_from_bitslice(atom.view_bits::<bitvec::order::Lsb0>());
+        }
+        bits.truncate(self.bit_count);
+        bits
+    }
+
+    /// Renders the bytes from [`Self::to_bytes`] as a hex dump.
+    ///
+    /// Each line holds up to 16 bytes, prefixed with the hexadecimal offset of its first byte,
+    /// e.g. `"0000: 88 82"`. Lines are separated by `'\n'`.
+    ///
+    /// # Returns
+    ///
+    /// The hex dump as a `String`.
+    #[must_use]
+    pub fn hex_dump(&self) -> String {
+        self.to_bytes()
+            .chunks(16)
+            .enumerate()
+            .map(|(line, bytes)| {
+                let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+                format!("{:04x}: {}", line * 16, hex.join(" "))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Renders the bits as Unicode Braille patterns, 8 bits per character.
+    ///
+    /// Bit `8 * n + k` of the array becomes dot `k + 1` of character `n`, using the Unicode
+    /// dot numbering: dots 1, 2, 3 run down the left column, dots 4, 5, 6 down the right
+    /// column, and dots 7 and 8 are the bottom row, left and right. A final group with fewer
+    /// than 8 bits leaves the remaining dots empty.
+    ///
+    /// # Returns
+    ///
+    /// A `String` with `bit_count.div_ceil(8)` characters from the range `U+2800..=U+28FF`.
+    #[must_use]
+    pub fn to_braille(&self) -> String {
+        self.to_bytes()
+            .into_iter()
+            .filter_map(|byte| char::from_u32(0x2800 + u32::from(byte)))
+            .collect()
+    }
+
+    /// Encodes the bits as runs of equal values.
+    ///
+    /// # Returns
+    ///
+    /// Alternating `(value, length)` pairs starting from index 0. The lengths add up to
+    /// `bit_count`.
+    #[must_use]
+    pub fn to_rle(&self) -> Vec<(bool, usize)> {
+        self.runs()
+            .map(|(value, _, length)| (value, length))
+            .collect()
+    }
+
+    /// Encodes the bits as runs of equal values in canonical form.
+    ///
+    /// The result is guaranteed to have no runs of length zero and no two adjacent pairs
+    /// with the same value, so equal arrays always produce equal encodings.
+    ///
+    /// # Returns
+    ///
+    /// Alternating `(value, length)` pairs starting from index 0. The lengths add up to
+    /// `bit_count`.
+    #[must_use]
+    pub fn to_rle_coalesced(&self) -> Vec<(bool, usize)> {
+        let mut pairs: Vec<(bool, usize)> = Vec::new();
+        for (value, _, length) in self.runs() {
+            if length == 0 {
+                continue;
+            }
+            match pairs.last_mut() {
+                Some((last_value, last_length)) if *last_value == value => *last_length += length,
+                _ => pairs.push((value, length)),
+            }
+        }
+        pairs
+    }
+
+    /// Finds the longest run of consecutive set bits.
+    ///
+    /// # Returns
+    ///
+    /// * The `(start, length)` of the longest run, the earliest one if several have the
+    ///   same length, or `None` if no bits are set.
+    #[must_use]
+    pub fn longest_set_run(&self) -> Option<(usize, usize)> {
+        self.longest_run(true)
+    }
+
+    /// Finds the longest run of consecutive unset bits.
+    ///
+    /// # Returns
+    ///
+    /// * The `(start, length)` of the longest run, the earliest one if several have the
+    ///   same length, or `None` if all bits are set.
+    #[must_use]
+    pub fn longest_unset_run(&self) -> Option<(usize, usize)> {
+        self.longest_run(false)
+    }
+
+    /// Finds the first run of at least `len` consecutive unset bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The number of consecutive unset bits needed.
+    ///
+    /// # Returns
+    ///
+    /// * The start index of the run, `Some(0)` if `len` is zero, or `None` if no run is
+    ///   long enough.
+    #[must_use]
+    pub fn find_unset_run(&self, len: usize) -> Option<usize> {
+        if len == 0 {
+            return Some(0);
+        }
+
+        let mut start = self.find_from(0, false)?;
+        loop {
+            let end = self.find_from(start, true).unwrap_or(self.bit_count);
+            if end - start >= len {
+                return Some(start);
+            }
+            start = self.find_from(end, false)?;
+        }
+    }
+
+    /// Finds the first run of at least `len` consecutive unset bits that starts at a
+    /// multiple of `align`.
+    ///
+    /// A run of unset bits that starts at an unaligned index is still used if an aligned
+    /// start inside it leaves at least `len` unset bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The number of consecutive unset bits needed.
+    /// * `align` - The required alignment of the start index, e.g. a power of two.
+    ///
+    /// # Returns
+    ///
+    /// * The aligned start index of the run, `Some(0)` if `len` is zero, or `None` if no
+    ///   aligned run is long enough.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `align` is zero.
+    #[must_use]
+    pub fn find_unset_run_aligned(&self, len: usize, align: usize) -> Option<usize> {
+        assert_ne!(align, 0, "align must be greater than zero");
+        if len == 0 {
+            return Some(0);
+        }
+
+        let mut start = self.find_from(0, false)?;
+        loop {
+            let end = self.find_from(start, true).unwrap_or(self.bit_count);
+            let aligned = start.checked_next_multiple_of(align)?;
+            if aligned
+                .checked_add(len)
+                .is_some_and(|run_end| run_end <= end)
+            {
+                return Some(aligned);
+            }
+            start = self.find_from(end, false)?;
+        }
+    }
+
+    /// Finds the first run of `len` consecutive unset bits and sets them.
+    ///
+    /// # Arguments
+    ///
+    /// * `len` - The number of consecutive bits to claim.
+    ///
+    /// # Returns
+    ///
+    /// * The start index of the claimed run, or `None` if no run is long enough.
+    pub fn claim_unset_run(&mut self, len: usize) -> Option<usize> {
+        let start = self.find_unset_run(len)?;
+        self.write_range(start, start + len, true);
+        Some(start)
+    }
+
+    /// Sets all bits in `start..end`, a whole atom at a time where possible.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first bit to set.
+    /// * `end` - The index one past the last bit to set.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `start > end` or `end > bit_count`.
+    pub fn set_range(&mut self, start: usize, end: usize) {
+        if let Err(err) = self.try_set_range(start, end) {
+            panic!("{err}");
+        }
+    }
+
+    /// Sets all bits in `start..end`, or reports an invalid range instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first bit to set.
+    /// * `end` - The index one past the last bit to set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BitArrayError::RangeOutOfBounds`] if `start > end` or `end > bit_count`.
+    /// The array is not modified in that case.
+    pub fn try_set_range(&mut self, start: usize, end: usize) -> Result<(), BitArrayError> {
+        self.check_range(start, end)?;
+        self.write_range(start, end, true);
+        Ok(())
+    }
+
+    /// Sets every `stride`-th bit in `start..end`, starting with the bit at `start`.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first bit to set.
+    /// * `end` - The index one past the last bit that may be set.
+    /// * `stride` - The distance between two set bits.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `stride` is zero, `start > end` or `end > bit_count`.
+    pub fn set_strided(&mut self, start: usize, end: usize, stride: usize) {
+        assert_ne!(stride, 0, "stride must be greater than zero");
+        if let Err(err) = self.check_range(start, end) {
+            panic!("{err}");
+        }
+        for index in (start..end).step_by(stride) {
+            self.set(index);
+        }
+    }
+
+    /// Inverts all bits in `start..end`, a whole atom at a time where possible.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The index of the first bit to invert.
+    /// * `end` - The index one past the last bit to invert.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `start > end` or `end > bit_count`.
+    pub fn invert_range(&mut self, start: usize, end: usize) {
+        if let Err(err) = self.check_range(start, end) {
+            panic!("{err}");
+        }
+
+        let mut index = start;
+        while index < end {
+            let bit_index = index % BIT_ARRAY_BITS_IN_ATOM;
+            let bits = (BIT_ARRAY_BITS_IN_ATOM - bit_index).min(end - index);
+            let mask = low_bits_mask(bits) << bit_index;
+
+            let atom = &mut self.array[index / BIT_ARRAY_BITS_IN_ATOM];
+            let previous = (*atom & mask).count_ones() as usize;
+            *atom ^= mask;
+            self.sub_from_count(previous);
+            self.add_to_count(bits - previous);
+            index += bits;
+        }
+    }
+
+    /// Changes the number of bits in the array.
+    ///
+    /// When growing, every bit from the old `bit_count` up to the new one is set to `fill`,
+    /// including the former padding bits of the old final atom.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_bit_count` - The new maximum number of bits in the array.
+    /// * `fill` - The value of the bits added when growing.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `new_bit_count` is zero.
+    pub fn resize(&mut self, new_bit_count: usize, fill: bool) {
+        assert_ne!(new_bit_count, 0, "bit_count must be greater than zero");
+        let old_bit_count = self.bit_count;
+        let old_atom_count = self.array.len();
+        let new_atom_count = new_bit_count.div_ceil(BIT_ARRAY_BITS_IN_ATOM);
+
+        if new_bit_count < old_bit_count {
+            let removed: usize = self.array[new_atom_count..]
+                .iter()
+                .map(|atom| atom.count_ones() as usize)
+                .sum();
+            self.array.truncate(new_atom_count);
+            self.bit_count = new_bit_count;
+
+            let dropped = self.array[new_atom_count - 1] & !self.final_atom_mask();
+            self.sub_from_count(removed + dropped.count_ones() as usize);
+            self.mask_final_atom();
+        } else if new_bit_count > old_bit_count {
+            let fill_atom = if fill { BitArrayAtom::MAX } else { 0 };
+            self.array.resize(new_atom_count, fill_atom);
+            self.bit_count = new_bit_count;
+
+            if fill {
+                let old_atoms_end = (old_atom_count * BIT_ARRAY_BITS_IN_ATOM).min(new_bit_count);
+                self.write_range(old_bit_count, old_atoms_end, true);
+                self.add_to_count(new_bit_count - old_atoms_end);
+                self.mask_final_atom();
+            }
+            self.notify_grow(old_bit_count);
+        }
+    }
+
+    /// Registers a callback that is invoked whenever the array grows.
+    ///
+    /// The callback receives the old and the new `bit_count`, and is called by
+    /// [`Self::push`], [`Self::append`] and by [`Self::resize`] when the array becomes
+    /// larger. It replaces any previously registered callback, and is not carried over
+    /// to clones.
+    ///
+    /// The bounds on [`GrowCallback`] keep the auto traits that `BitArray` has without a
+    /// callback, since the boxed closure is stored in the array:
+    ///
+    /// * `Send` - so the array can still be moved to another thread.
+    /// * `Sync` - so `&BitArray` can still be shared between threads.
+    /// * `UnwindSafe` - so the array can still be moved into `catch_unwind`.
+    /// * `RefUnwindSafe` - so `&BitArray` can still be used inside `catch_unwind`.
+    ///
+    /// The callback can therefore not capture `Rc` or `Cell` state; share state through
+    /// `Arc` with a `Mutex` or atomics instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The callback to invoke with `(old_bit_count, new_bit_count)`.
+    pub fn set_on_grow(&mut self, f: GrowCallback) {
+        self.on_grow = Some(f);
+    }
+
+    fn notify_grow(&mut self, old_bit_count: usize) {
+        if let Some(on_grow) = self.on_grow.as_mut() {
+            on_grow(old_bit_count, self.bit_count);
+        }
+    }
+
+    /// Changes the number of bits in the array, clearing any bits that are dropped.
+    ///
+    /// Same as `resize(new_bit_count, false)`. The backing storage is only reallocated when
+    /// the number of atoms changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_bit_count` - The new maximum number of bits in the array.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `new_bit_count` is zero.
+    pub fn set_bit_count(&mut self, new_bit_count: usize) {
+        self.resize(new_bit_count, false);
+    }
+
+    /// Grows the array so that `bit_count` becomes a multiple of `multiple`.
+    ///
+    /// Does nothing if `bit_count` already is a multiple of `multiple`.
+    ///
+    /// # Arguments
+    ///
+    /// * `multiple` - The value that the new `bit_count` must be a multiple of.
+    /// * `fill` - The value of the bits added when growing.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `multiple` is zero.
+    pub fn pad_to_multiple_of(&mut self, multiple: usize, fill: bool) {
+        assert_ne!(multiple, 0, "multiple must be greater than zero");
+        self.resize(self.bit_count.next_multiple_of(multiple), fill);
+    }
+
+    /// Returns a resized copy of the array, leaving the original untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `new_bit_count` - The maximum number of bits in the copy.
+    /// * `fill` - The value of the bits added when growing.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `new_bit_count` is zero.
+    #[must_use]
+    pub fn clone_resized(&self, new_bit_count: usize, fill: bool) -> Self {
+        let mut result = self.clone();
+        result.resize(new_bit_count, fill);
+        result
+    }
+
+    /// Shifts every bit one index higher and inserts a bit at index zero, like a shift
+    /// register.
+    ///
+    /// # Arguments
+    ///
+    /// * `bit` - The value of the new bit at index zero.
+    ///
+    /// # Returns
+    ///
+    /// The bit that was shifted out, i.e. the previous bit at `bit_count - 1`.
+    pub fn shift_in(&mut self, bit: bool) -> bool {
+        let shifted_out = self.get(self.bit_count - 1);
+
+        let mut carry = BitArrayAtom::from(bit);
+        for atom in &mut self.array {
+            let next_carry = *atom >> (BIT_ARRAY_BITS_IN_ATOM - 1);
+            *atom = (*atom << 1) | carry;
+            carry = next_carry;
+        }
+        self.mask_final_atom();
+
+        self.add_to_count(usize::from(bit));
+        self.sub_from_count(usize::from(shifted_out));
+        shifted_out
+    }
+
+    /// Appends a bit to the end of the array.
+    ///
+    /// The backing storage grows with amortized doubling, so pushing N bits is linear in N.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value of the new bit.
+    pub fn push(&mut self, value: bool) {
+        if self.bit_count.is_multiple_of(BIT_ARRAY_BITS_IN_ATOM) {
+            self.array.push(0);
+        }
+        self.bit_count += 1;
+        self.set_bit(self.bit_count - 1, value);
+        self.notify_grow(self.bit_count - 1);
+    }
+
+    /// Sets or unsets the bit at the given index, first growing the array if needed.
+    ///
+    /// If the array is shorter than `max(index + 1, min_bit_count)` bits, it is grown to
+    /// that size with the new bits unset, so callers can grow in larger steps than one bit.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The zero-based index of the bit to modify.
+    /// * `value` - If `true`, the bit will be set (1). If `false`, the bit will be unset (0).
+    /// * `min_bit_count` - The minimum `bit_count` to grow to.
+    pub fn set_bit_grow(&mut self, index: usize, value: bool, min_bit_count: usize) {
+        let needed_bit_count = (index + 1).max(min_bit_count);
+        if needed_bit_count > self.bit_count {
+            self.resize(needed_bit_count, false);
+        }
+        self.set_bit(index, value);
+    }
+
+    /// Reserves capacity for at least `additional_bits` more bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `additional_bits` - The number of bits expected to be added, e.g. by [`Self::push`].
+    pub fn reserve(&mut self, additional_bits: usize) {
+        let atom_count = (self.bit_count + additional_bits).div_ceil(BIT_ARRAY_BITS_IN_ATOM);
+        self.array.reserve(atom_count - self.array.len());
+    }
+
+    /// Reserves capacity for exactly `additional_bits` more bits, rounded up to whole atoms.
+    ///
+    /// Unlike [`Self::reserve`], this does not over-allocate to amortize future growth.
+    ///
+    /// # Arguments
+    ///
+    /// * `additional_bits` - The number of bits expected to be added, e.g. by [`Self::push`].
+    pub fn reserve_exact(&mut self, additional_bits: usize) {
+        let atom_count = (self.bit_count + additional_bits).div_ceil(BIT_ARRAY_BITS_IN_ATOM);
+        self.array.reserve_exact(atom_count - self.array.len());
+    }
+
+    /// Appends the bits of another `BitArray` to the end of this one.
+    ///
+    /// The backing storage grows with amortized doubling, so repeated appends are linear
+    /// in the total number of bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` whose bits are appended.
+    pub fn append(&mut self, other: &Self) {
+        let offset = self.bit_count;
+        let new_bit_count = offset + other.bit_count;
+        self.array
+            .resize(new_bit_count.div_ceil(BIT_ARRAY_BITS_IN_ATOM), 0);
+
+        let base = offset / BIT_ARRAY_BITS_IN_ATOM;
+        let shift = offset % BIT_ARRAY_BITS_IN_ATOM;
+        for (i, &atom) in other.array.iter().enumerate() {
+            self.array[base + i] |= atom << shift;
+            if shift != 0 {
+                if let Some(next) = self.array.get_mut(base + i + 1) {
+                    *next |= atom >> (BIT_ARRAY_BITS_IN_ATOM - shift);
+                }
+            }
+        }
+
+        self.bit_count = new_bit_count;
+        self.add_to_count(other.count_set_bits());
+        if new_bit_count > offset {
+            self.notify_grow(offset);
+        }
+    }
+
+    /// Returns the number of runs of consecutive set bits.
+    ///
+    /// # Returns
+    ///
+    /// The number of positions where a run of set bits starts.
+    #[must_use]
+    pub fn count_set_runs(&self) -> usize {
+        let mut previous_top_bit = 0;
+        let mut runs = 0;
+        for &atom in &self.array {
+            let starts = atom & !((atom << 1) | previous_top_bit);
+            runs += starts.count_ones() as usize;
+            previous_top_bit = atom >> (BIT_ARRAY_BITS_IN_ATOM - 1);
+        }
+        runs
+    }
+
+    /// Interleaves the bits of two `BitArray`s.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` whose bits end up at the odd indices.
+    ///
+    /// # Returns
+    ///
+    /// A `BitArray` of twice the `bit_count`, with bit `i` of `self` at index `2 * i` and
+    /// bit `i` of `other` at index `2 * i + 1`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the two arrays have different `bit_count`.
+    #[must_use]
+    pub fn interleave(&self, other: &Self) -> Self {
+        self.assert_same_len(other);
+        let mut result = Self::new(self.bit_count + other.bit_count);
+        for index in self.iter_set_bits() {
+            result.set(2 * index);
+        }
+        for index in other.iter_set_bits() {
+            result.set(2 * index + 1);
+        }
+        result
+    }
+
+    /// Splits the bits at even and odd indices into two `BitArray`s.
+    ///
+    /// This is the inverse of [`Self::interleave`]. With an odd `bit_count`, the trailing bit
+    /// is at an even index and goes to the first array.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of a `BitArray` with the bits at even indices, holding `bit_count.div_ceil(2)`
+    /// bits, and a `BitArray` with the bits at odd indices, holding `bit_count / 2` bits.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `bit_count` is less than two.
+    #[must_use]
+    pub fn deinterleave(&self) -> (Self, Self) {
+        let mut even = Self::new(self.bit_count.div_ceil(2));
+        let mut odd = Self::new(self.bit_count / 2);
+        for index in self.iter_set_bits() {
+            if index % 2 == 0 {
+                even.set(index / 2);
+            } else {
+                odd.set(index / 2);
+            }
+        }
+        (even, odd)
+    }
+
+    /// Returns the number of leading bits that are equal in both arrays.
+    ///
+    /// The arrays may have different `bit_count`, only the bits present in both are
+    /// compared.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` to compare against.
+    ///
+    /// # Returns
+    ///
+    /// The index of the first bit that differs, or the smaller `bit_count` if no bit differs.
+    #[must_use]
+    pub fn common_prefix_len(&self, other: &Self) -> usize {
+        let bit_count = self.bit_count.min(other.bit_count);
+        self.array
+            .iter()
+            .zip(&other.array)
+            .position(|(a, b)| a != b)
+            .map_or(bit_count, |atom_index| {
+                let difference = self.array[atom_index] ^ other.array[atom_index];
+                let index =
+                    atom_index * BIT_ARRAY_BITS_IN_ATOM + difference.trailing_zeros() as usize;
+                index.min(bit_count)
+            })
+    }
+
+    /// Compares this `BitArray` to a later snapshot of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` to compare against.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the indices of the bits that are set in `other` but not in `self`, and the
+    /// indices of the bits that are set in `self` but not in `other`, both in ascending order.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the two arrays have different `bit_count`.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> (Vec<usize>, Vec<usize>) {
+        self.assert_same_len(other);
+        let mut newly_set = Vec::new();
+        let mut newly_cleared = Vec::new();
+        for (atom_index, (&before, &after)) in self.array.iter().zip(&other.array).enumerate() {
+            push_atom_indices(&mut newly_set, atom_index, after & !before);
+            push_atom_indices(&mut newly_cleared, atom_index, before & !after);
+        }
+        (newly_set, newly_cleared)
+    }
+
+    /// Performs an in-place bitwise OR with another `BitArray`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` to combine with.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the two arrays have different `bit_count`.
+    pub fn or_assign(&mut self, other: &Self) {
+        self.assert_same_len(other);
+        for (atom, &other_atom) in self.array.iter_mut().zip(&other.array) {
+            *atom |= other_atom;
+        }
+        self.recount_set_bits();
+    }
+
+    /// Performs an in-place bitwise OR with another, possibly shorter, `BitArray` placed at
+    /// a bit offset.
+    ///
+    /// Bit `i` of `other` is combined into bit `offset + i` of `self`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` to combine with.
+    /// * `offset` - The index in `self` where the first bit of `other` is placed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `offset + other.bit_count()` is greater than `bit_count`.
+    pub fn or_at(&mut self, other: &Self, offset: usize) {
+        assert!(
+            offset + other.bit_count <= self.bit_count,
+            "Destination range out of bounds"
+        );
+
+        let base = offset / BIT_ARRAY_BITS_IN_ATOM;
+        let shift = offset % BIT_ARRAY_BITS_IN_ATOM;
+        for (i, &atom) in other.array.iter().enumerate() {
+            self.array[base + i] |= atom << shift;
+            if shift != 0 {
+                if let Some(next) = self.array.get_mut(base + i + 1) {
+                    *next |= atom >> (BIT_ARRAY_BITS_IN_ATOM - shift);
+                }
+            }
+        }
+        self.recount_set_bits();
+    }
+
+    /// Performs an in-place bitwise AND with another `BitArray`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` to combine with.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the two arrays have different `bit_count`.
+    pub fn and_assign(&mut self, other: &Self) {
+        self.assert_same_len(other);
+        for (atom, &other_atom) in self.array.iter_mut().zip(&other.array) {
+            *atom &= other_atom;
+        }
+        self.recount_set_bits();
+    }
+
+    /// Performs an in-place bitwise XOR with another `BitArray`.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` to combine with.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the two arrays have different `bit_count`.
+    pub fn xor_assign(&mut self, other: &Self) {
+        self.assert_same_len(other);
+        for (atom, &other_atom) in self.array.iter_mut().zip(&other.array) {
+            *atom ^= other_atom;
+        }
+        self.recount_set_bits();
+    }
+
+    /// Performs an in-place bitwise AND with a repeating 32-bit pattern.
+    ///
+    /// Bit `i` of the array is combined with bit `i % 32` of `value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The pattern to combine with.
+    pub fn and_with_u32(&mut self, value: u32) {
+        let pattern = repeat_u32(value);
+        for atom in &mut self.array {
+            *atom &= pattern;
+        }
+        self.recount_set_bits();
+    }
+
+    /// Performs an in-place bitwise OR with a repeating 32-bit pattern.
+    ///
+    /// Bit `i` of the array is combined with bit `i % 32` of `value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The pattern to combine with.
+    pub fn or_with_u32(&mut self, value: u32) {
+        let pattern = repeat_u32(value);
+        for atom in &mut self.array {
+            *atom |= pattern;
+        }
+        self.mask_final_atom();
+        self.recount_set_bits();
+    }
+
+    /// Performs an in-place bitwise XOR with a repeating 32-bit pattern.
+    ///
+    /// Bit `i` of the array is combined with bit `i % 32` of `value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The pattern to combine with.
+    pub fn xor_with_u32(&mut self, value: u32) {
+        let pattern = repeat_u32(value);
+        for atom in &mut self.array {
+            *atom ^= pattern;
+        }
+        self.mask_final_atom();
+        self.recount_set_bits();
+    }
+
+    /// Copies a range of bits from another `BitArray` into this one.
+    ///
+    /// The bits are copied a whole atom at a time, also when the offsets are not aligned to
+    /// atoms. `src` can not borrow `self`, so to move bits within the same array, copy from a
+    /// clone of it; overlapping ranges are then handled like `memmove`.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The `BitArray` to copy from.
+    /// * `src_start` - The index of the first bit to copy in `src`.
+    /// * `dst_start` - The index in `self` that receives the first copied bit.
+    /// * `len` - The number of bits to copy.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if either range is out of bounds.
+    pub fn copy_bits_from(&mut self, src: &Self, src_start: usize, dst_start: usize, len: usize) {
+        assert!(
+            src_start <= src.bit_count && len <= src.bit_count - src_start,
+            "Source range out of bounds"
+        );
+        assert!(
+            dst_start <= self.bit_count && len <= self.bit_count - dst_start,
+            "Destination range out of bounds"
+        );
+
+        let mut copied = 0;
+        while copied < len {
+            let count = (len - copied).min(BIT_ARRAY_BITS_IN_ATOM);
+            let value = src.read_bits(src_start + copied, count);
+            self.write_bits(dst_start + copied, count, value);
+            copied += count;
+        }
+    }
+
+    /// Returns the bitwise XOR of two `BitArray`s of possibly different lengths.
+    ///
+    /// The shorter operand is treated as if zero-extended to the longer one.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` to combine with.
+    ///
+    /// # Returns
+    ///
+    /// A `BitArray` with the larger of the two `bit_count`s.
+    #[must_use]
+    pub fn xor_extended(&self, other: &Self) -> Self {
+        self.combine_extended(other, |a, b| a ^ b)
+    }
+
+    /// Returns the bitwise AND of two `BitArray`s of possibly different lengths.
+    ///
+    /// The shorter operand is treated as if zero-extended to the longer one, so no bits are
+    /// set beyond the length of the shorter one.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` to combine with.
+    ///
+    /// # Returns
+    ///
+    /// A `BitArray` with the larger of the two `bit_count`s.
+    #[must_use]
+    pub fn and_extended(&self, other: &Self) -> Self {
+        self.combine_extended(other, |a, b| a & b)
+    }
+
+    /// Returns the bitwise OR of two `BitArray`s of possibly different lengths.
+    ///
+    /// The shorter operand is treated as if zero-extended to the longer one.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` to combine with.
+    ///
+    /// # Returns
+    ///
+    /// A `BitArray` with the larger of the two `bit_count`s.
+    #[must_use]
+    pub fn or_extended(&self, other: &Self) -> Self {
+        self.combine_extended(other, |a, b| a | b)
+    }
+
+    /// Combines another `BitArray` into this one using a custom bit operation.
+    ///
+    /// The operation is applied bit by bit, so prefer [`Self::or_assign`], [`Self::and_assign`]
+    /// or [`Self::xor_assign`] for the standard operations.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The `BitArray` to combine with.
+    /// * `op` - Called with the bit from `self` and the bit from `other`, returns the new bit value.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the two arrays have different `bit_count`.
+    pub fn merge_with(&mut self, other: &Self, op: impl Fn(bool, bool) -> bool) {
+        self.assert_same_len(other);
+        for index in 0..self.bit_count {
+            let array_index = index / BIT_ARRAY_BITS_IN_ATOM;
+            let mask = 1 << (index % BIT_ARRAY_BITS_IN_ATOM);
+            if op(self.get(index), other.get(index)) {
+                self.array[array_index] |= mask;
+            } else {
+                self.array[array_index] &= !mask;
+            }
+        }
+        self.recount_set_bits();
+    }
+
+    /// Combines the atoms of two arrays, zero-extending the shorter one.
+    fn combine_extended(
+        &self,
+        other: &Self,
+        op: impl Fn(BitArrayAtom, BitArrayAtom) -> BitArrayAtom,
+    ) -> Self {
+        let atom_count = self.array.len().max(other.array.len());
+        let atoms: Vec<BitArrayAtom> = (0..atom_count)
+            .map(|i| {
+                let a = self.array.get(i).copied().unwrap_or(0);
+                let b = other.array.get(i).copied().unwrap_or(0);
+                op(a, b)
+            })
+            .collect();
+        Self::from_atoms(self.bit_count.max(other.bit_count), &atoms)
+    }
+
+    /// Checks that `other` has the same `bit_count` as `self`.
+    const fn check_same_len(&self, other: &Self) -> Result<(), BitArrayError> {
+        if self.bit_count == other.bit_count {
+            Ok(())
+        } else {
+            Err(BitArrayError::LengthMismatch {
+                left: self.bit_count,
+                right: other.bit_count,
+            })
+        }
+    }
+
+    /// Checks that `start..end` is a valid, possibly empty, range of bit indices.
+    const fn check_range(&self, start: usize, end: usize) -> Result<(), BitArrayError> {
+        if start <= end && end <= self.bit_count {
+            Ok(())
+        } else {
+            Err(BitArrayError::RangeOutOfBounds {
+                start,
+                end,
+                bit_count: self.bit_count,
+            })
+        }
+    }
+
+    /// Panics if `index` is not a valid bit index, naming both the index and `bit_count`.
+    #[inline]
+    #[track_caller]
+    fn assert_in_bounds(&self, index: usize) {
+        assert!(
+            index < self.bit_count,
+            "bit index {index} out of bounds for BitArray of {} bits",
+            self.bit_count
+        );
+    }
+
+    /// Panics if `other` does not have the same `bit_count` as `self`.
+    fn assert_same_len(&self, other: &Self) {
+        if let Err(err) = self.check_same_len(other) {
+            panic!("{err}");
+        }
+    }
+
+    /// Returns the mask of the valid bits in the final atom.
+    ///
+    /// When `bit_count` is a multiple of the atom size the final atom has no padding, so
+    /// the mask is all ones rather than `(1 << 0) - 1`. Every helper that masks the final
+    /// atom goes through this function.
+    const fn final_atom_mask(&self) -> BitArrayAtom {
+        let used_bits = self.bit_count % BIT_ARRAY_BITS_IN_ATOM;
+        if used_bits == 0 {
+            BitArrayAtom::MAX
+        } else {
+            (1 << used_bits) - 1
+        }
+    }
+
+    /// Returns the mask of the valid bits in the atom at `atom_index`.
+    const fn atom_mask(&self, atom_index: usize) -> BitArrayAtom {
+        if atom_index + 1 == self.array.len() {
+            self.final_atom_mask()
+        } else {
+            BitArrayAtom::MAX
+        }
+    }
+
+    /// Returns the backing atoms with the padding bits beyond `bit_count` cleared.
+    fn masked_atoms(&self) -> impl Iterator<Item = BitArrayAtom> + '_ {
+        self.array
+            .iter()
+            .enumerate()
+            .map(|(atom_index, &atom)| atom & self.atom_mask(atom_index))
+    }
+
+    /// Clears the padding bits beyond `bit_count` in the final atom.
+    fn mask_final_atom(&mut self) {
+        let mask = self.final_atom_mask();
+        if let Some(last) = self.array.last_mut() {
+            *last &= mask;
+        }
+    }
+
+    /// Reads `count` bits, at most one atom, starting at `start`, least significant bit first.
+    fn read_bits(&self, start: usize, count: usize) -> BitArrayAtom {
+        read_atom_bits(&self.array, start, count)
+    }
+
+    /// Overwrites `count` bits, at most one atom, starting at `start`, updating
+    /// `number_of_bits_set`.
+    fn write_bits(&mut self, start: usize, count: usize, value: BitArrayAtom) {
+        let array_index = start / BIT_ARRAY_BITS_IN_ATOM;
+        let shift = start % BIT_ARRAY_BITS_IN_ATOM;
+        let first_count = (BIT_ARRAY_BITS_IN_ATOM - shift).min(count);
+
+        self.write_atom_bits(array_index, shift, first_count, value);
+        if count > first_count {
+            self.write_atom_bits(
+                array_index + 1,
+                0,
+                count - first_count,
+                value >> first_count,
+            );
+        }
+    }
+
+    /// Overwrites `count` bits of the atom at `array_index` starting at bit `shift`.
+    fn write_atom_bits(
+        &mut self,
+        array_index: usize,
+        shift: usize,
+        count: usize,
+        value: BitArrayAtom,
+    ) {
+        let mask = low_bits_mask(count) << shift;
+        let atom = &mut self.array[array_index];
+        let previous = *atom & mask;
+        let next = (value << shift) & mask;
+        *atom = (*atom & !mask) | next;
+
+        self.sub_from_count(previous.count_ones() as usize);
+        self.add_to_count(next.count_ones() as usize);
+    }
+
+    /// Sets or clears all bits in `start..end` atom by atom, updating `number_of_bits_set`.
+    fn write_range(&mut self, start: usize, end: usize, value: bool) {
+        let mut index = start;
+        while index < end {
+            let bit_index = index % BIT_ARRAY_BITS_IN_ATOM;
+            let bits = (BIT_ARRAY_BITS_IN_ATOM - bit_index).min(end - index);
+            let mask = low_bits_mask(bits) << bit_index;
+
+            let atom = &mut self.array[index / BIT_ARRAY_BITS_IN_ATOM];
+            let previous = (*atom & mask).count_ones() as usize;
+            if value {
+                *atom |= mask;
+                self.add_to_count(bits - previous);
+            } else {
+                *atom &= !mask;
+                self.sub_from_count(previous);
+            }
+            index += bits;
+        }
+    }
+
+    /// Finds the first bit at or after `from` that equals `value`.
+    fn find_from(&self, from: usize, value: bool) -> Option<usize> {
+        if from >= self.bit_count {
+            return None;
+        }
+
+        let search_atom = |array_index: usize| {
+            let atom = self.array[array_index];
+            if value {
+                atom
+            } else {
+                !atom
+            }
+        };
+
+        let mut array_index = from / BIT_ARRAY_BITS_IN_ATOM;
+        let mut atom =
+            search_atom(array_index) & (BitArrayAtom::MAX << (from % BIT_ARRAY_BITS_IN_ATOM));
+        loop {
+            if atom != 0 {
+                let index = array_index * BIT_ARRAY_BITS_IN_ATOM + atom.trailing_zeros() as usize;
+                return (index < self.bit_count).then_some(index);
+            }
+            array_index += 1;
+            if array_index == self.array.len() {
+                return None;
+            }
+            atom = search_atom(array_index);
+        }
+    }
+
+    /// Returns an iterator over the runs of equal bits as `(value, start, length)`.
+    fn runs(&self) -> impl Iterator<Item = (bool, usize, usize)> + '_ {
+        let mut start = 0;
+        std::iter::from_fn(move || {
+            if start >= self.bit_count {
+                return None;
+            }
+            let value = self.get(start);
+            let end = self.find_from(start, !value).unwrap_or(self.bit_count);
+            let run = (value, start, end - start);
+            start = end;
+            Some(run)
+        })
+    }
+
+    /// Finds the earliest of the longest runs of bits equal to `value` as `(start, length)`.
+    fn longest_run(&self, value: bool) -> Option<(usize, usize)> {
+        self.runs()
+            .filter(|&(run_value, _, _)| run_value == value)
+            .map(|(_, start, length)| (start, length))
+            .min_by_key(|&(_, length)| std::cmp::Reverse(length))
+    }
+
+    /// Restores the invariants after modifying the atoms through [`Self::atoms_mut`].
+    ///
+    /// Clears the bits beyond `bit_count` in the final atom and recomputes the number of set
+    /// bits.
+    pub fn normalize(&mut self) {
+        self.mask_final_atom();
+        self.recount_set_bits();
+    }
+
+    /// Recomputes the number of set bits from the backing atoms.
+    ///
+    /// Must be called after modifying the atoms through [`Self::atoms_mut`].
+    pub const fn recount_set_bits(&mut self) {
+        self.number_of_bits_set = self.count_atom_ones();
+    }
+
+    /// Counts the set bits in the backing atoms, ignoring the cached count.
+    const fn count_atom_ones(&self) -> usize {
+        let atoms = self.array.as_slice();
+        let mut count = 0;
+        let mut atom_index = 0;
+        while atom_index < atoms.len() {
+            count += atoms[atom_index].count_ones() as usize;
+            atom_index += 1;
+        }
+        count
+    }
+
+    /// Adds to the cached number of set bits, unless count tracking is disabled.
+    const fn add_to_count(&mut self, count: usize) {
+        if self.count_tracking {
+            self.number_of_bits_set += count;
+        }
+    }
+
+    /// Subtracts from the cached number of set bits, unless count tracking is disabled.
+    const fn sub_from_count(&mut self, count: usize) {
+        if self.count_tracking {
+            self.number_of_bits_set -= count;
+        }
+    }
+}
+
+impl Index<usize> for BitArray {
+    type Output = bool;
+    /// Provides indexed access to individual bits in the `BitArray`.
+    ///
+    /// Indexing is read-only and `IndexMut` is intentionally not implemented, since bits are
+    /// not addressable. Use [`BitArray::set_bit`] to modify a bit.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The zero-based index of the bit to access.
+    ///
+    /// # Returns
+    ///
+    /// A reference to a boolean value (`true` or `false`) representing the state of the bit
+    /// at the specified index.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the `index` is out of bounds.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bit_array_rs::BitArray;
+    /// let mut bit_array = BitArray::new(16);
+    /// bit_array.set(3);
+    /// assert_eq!(bit_array[3], true);
+    /// assert_eq!(bit_array[0], false);
+    /// ```
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get_ref(index)
+    }
+}
+
+impl PartialEq for BitArray {
+    /// Two `BitArray`s are equal if they have the same `bit_count` and the same bits set.
+    ///
+    /// When both arrays track their number of set bits, arrays with a different count are
+    /// rejected without comparing the atoms. Otherwise the atoms are compared pairwise,
+    /// ignoring the padding bits beyond `bit_count`.
+    fn eq(&self, other: &Self) -> bool {
+        let counts_match = !(self.count_tracking && other.count_tracking)
+            || self.number_of_bits_set == other.number_of_bits_set;
+        self.bit_count == other.bit_count
+            && counts_match
+            && self.masked_atoms().eq(other.masked_atoms())
+    }
+}
+
+impl Eq for BitArray {}
+
+impl PartialOrd for BitArray {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BitArray {
+    /// Orders `BitArray`s by `bit_count` first, then by the atoms from the lowest atom index.
+    /// The padding bits beyond `bit_count` are ignored.
+    ///
+    /// This is a total order, e.g. for use in a `BTreeSet`, and says nothing about whether
+    /// one array is a subset of another.
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.bit_count
+            .cmp(&other.bit_count)
+            .then_with(|| self.masked_atoms().cmp(other.masked_atoms()))
+    }
+}
+
+impl BitOrAssign<&Self> for BitArray {
+    /// Performs `a |= &b`, see [`BitArray::or_assign`].
+    fn bitor_assign(&mut self, rhs: &Self) {
+        self.or_assign(rhs);
+    }
+}
+
+impl BitAndAssign<&Self> for BitArray {
+    /// Performs `a &= &b`, see [`BitArray::and_assign`].
+    fn bitand_assign(&mut self, rhs: &Self) {
+        self.and_assign(rhs);
+    }
+}
+
+impl BitXorAssign<&Self> for BitArray {
+    /// Performs `a ^= &b`, see [`BitArray::xor_assign`].
+    fn bitxor_assign(&mut self, rhs: &Self) {
+        self.xor_assign(rhs);
+    }
+}
+
+impl std::fmt::Debug for BitArray {
+    /// Formats the `BitArray` as a binary string with groups of 8 bits separated by a space.
+    ///
+    /// A space is written before every bit whose index is a non-zero multiple of 8, so the
+    /// output never starts or ends with a space, and a final group may have fewer than 8 bits.
+    ///
+    /// # Arguments
+    ///
+    /// * `f` - The formatter used to output the debug string.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating whether the formatting was successful.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use bit_array_rs::BitArray;
+    /// let mut bit_array = BitArray::new(16);
     /// bit_array.set(3);
     /// bit_array.set(7);
     /// bit_array.set(9);
@@ -303,11 +2826,22 @@ impl std::fmt::Debug for BitArray {
     /// assert_eq!(format!("{:?}", bit_array), "00010001 01000001");
     /// ```
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for i in 0..self.bit_count {
-            if i > 0 && i % 8 == 0 {
-                write!(f, " ")?;
+        // Each atom is rendered into a stack buffer, 64 digits and at most 8 spaces, and
+        // written with a single call instead of one call per bit.
+        let mut buf = [0u8; BIT_ARRAY_BITS_IN_ATOM + BIT_ARRAY_BITS_IN_ATOM / 8];
+        for (atom_index, &atom) in self.array.iter().enumerate() {
+            let base = atom_index * BIT_ARRAY_BITS_IN_ATOM;
+            let bits = (self.bit_count - base).min(BIT_ARRAY_BITS_IN_ATOM);
+            let mut len = 0;
+            for bit in 0..bits {
+                if base + bit > 0 && bit % 8 == 0 {
+                    buf[len] = b' ';
+                    len += 1;
+                }
+                buf[len] = if (atom >> bit) & 1 == 0 { b'0' } else { b'1' };
+                len += 1;
             }
-            write!(f, "{}", u8::from(self.get(i)))?;
+            f.write_str(std::str::from_utf8(&buf[..len]).map_err(|_| std::fmt::Error)?)?;
         }
         Ok(())
     }
@@ -316,6 +2850,9 @@ impl std::fmt::Debug for BitArray {
 impl std::fmt::Display for BitArray {
     /// Formats the `BitArray` as a continuous binary string without any spaces.
     ///
+    /// With the alternate flag (`{:#}`) the bits are grouped by 8, separated by a space,
+    /// matching the `Debug` output.
+    ///
     /// # Arguments
     ///
     /// * `f` - The formatter used to output the display string.
@@ -335,11 +2872,381 @@ impl std::fmt::Display for BitArray {
     /// bit_array.set(15);
     ///
     /// assert_eq!(format!("{}", bit_array), "0001000101000001");
+    /// assert_eq!(format!("{:#}", bit_array), "00010001 01000001");
     /// ```
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let grouped = f.alternate();
         for i in 0..self.bit_count {
+            if grouped && i > 0 && i % 8 == 0 {
+                write!(f, " ")?;
+            }
             write!(f, "{}", u8::from(self.get(i)))?;
         }
         Ok(())
     }
 }
+
+impl<'a> IntoIterator for &'a BitArray {
+    type Item = bool;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl FromIterator<bool> for BitArray {
+    /// Collects bit values into a `BitArray`, with the first value at index zero.
+    ///
+    /// The backing storage is allocated up front from the lower bound of the iterator's
+    /// size hint, so collecting from an iterator of known length allocates once.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the iterator yields no values.
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let (lower, _) = iter.size_hint();
+        let mut array = Vec::with_capacity(lower.div_ceil(BIT_ARRAY_BITS_IN_ATOM));
+        let mut bit_count = 0;
+        let mut number_of_bits_set = 0;
+
+        for value in iter {
+            let bit_index = bit_count % BIT_ARRAY_BITS_IN_ATOM;
+            if bit_index == 0 {
+                array.push(0);
+            }
+            if value {
+                if let Some(atom) = array.last_mut() {
+                    *atom |= 1 << bit_index;
+                }
+                number_of_bits_set += 1;
+            }
+            bit_count += 1;
+        }
+        assert_ne!(bit_count, 0, "bit_count must be greater than zero");
+
+        Self {
+            array,
+            bit_count,
+            number_of_bits_set,
+            count_tracking: true,
+            on_grow: None,
+        }
+    }
+}
+
+/// An iterator over the bit values in a [`BitArray`].
+///
+/// Created by [`BitArray::iter`].
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    bit_array: &'a BitArray,
+    front: usize,
+    back: usize,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = bool;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let value = self.bit_array.get(self.front);
+        self.front += 1;
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.back - self.front;
+        (remaining, Some(remaining))
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.bit_array.get(self.back))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// An iterator over the indices of the set bits in a [`BitArray`].
+///
+/// Each step extracts the lowest (or highest) set bit of a local copy of an atom, so the
+/// cost is proportional to the number of set bits rather than the number of bits.
+///
+/// Created by [`BitArray::iter_set_bits`].
+#[derive(Debug, Clone)]
+pub struct SetBitsIter<'a> {
+    /// Atoms not yet loaded into `front_atom` or `back_atom`.
+    atoms: &'a [BitArrayAtom],
+    middle_start: usize,
+    front_atom: BitArrayAtom,
+    front_base: usize,
+    back_atom: BitArrayAtom,
+    back_base: usize,
+}
+
+impl SetBitsIter<'_> {
+    /// Removes the lowest set bit from `atom` and returns its index.
+    const fn take_lowest(atom: &mut BitArrayAtom, base: usize) -> usize {
+        let lowest = *atom & atom.wrapping_neg();
+        *atom ^= lowest;
+        base + lowest.trailing_zeros() as usize
+    }
+
+    /// Removes the highest set bit from `atom` and returns its index.
+    const fn take_highest(atom: &mut BitArrayAtom, base: usize) -> usize {
+        let bit = BIT_ARRAY_BITS_IN_ATOM - 1 - atom.leading_zeros() as usize;
+        *atom &= !(1 << bit);
+        base + bit
+    }
+}
+
+impl Iterator for SetBitsIter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.front_atom != 0 {
+                return Some(Self::take_lowest(&mut self.front_atom, self.front_base));
+            }
+            if let Some((&atom, rest)) = self.atoms.split_first() {
+                self.front_atom = atom;
+                self.front_base = self.middle_start * BIT_ARRAY_BITS_IN_ATOM;
+                self.middle_start += 1;
+                self.atoms = rest;
+            } else if self.back_atom != 0 {
+                return Some(Self::take_lowest(&mut self.back_atom, self.back_base));
+            } else {
+                return None;
+            }
+        }
+    }
+}
+
+impl DoubleEndedIterator for SetBitsIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.back_atom != 0 {
+                return Some(Self::take_highest(&mut self.back_atom, self.back_base));
+            }
+            if let Some((&atom, rest)) = self.atoms.split_last() {
+                self.back_atom = atom;
+                self.back_base = (self.middle_start + rest.len()) * BIT_ARRAY_BITS_IN_ATOM;
+                self.atoms = rest;
+            } else if self.front_atom != 0 {
+                return Some(Self::take_highest(&mut self.front_atom, self.front_base));
+            } else {
+                return None;
+            }
+        }
+    }
+}
+
+/// A borrowed read-only view of a range of bits in a [`BitArray`].
+///
+/// Created by [`BitArray::view`]. Indices are relative to the start of the view.
+#[derive(Debug, Clone, Copy)]
+pub struct BitSlice<'a> {
+    atoms: &'a [BitArrayAtom],
+    start: usize,
+    len: usize,
+}
+
+impl<'a> BitSlice<'a> {
+    /// Returns the number of bits in the view.
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Checks if the view holds no bits.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the bit value at the specified index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The bit index, relative to the start of the view.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    #[must_use]
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len, "Index out of bounds");
+        read_atom_bits(self.atoms, self.start + index, 1) != 0
+    }
+
+    /// Returns the number of bits in the view that are set.
+    #[must_use]
+    pub fn count_set_bits(&self) -> usize {
+        self.chunks()
+            .map(|(_, chunk)| chunk.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns an iterator over the indices of the set bits, relative to the start of the view.
+    pub fn iter_set_bits(&self) -> impl Iterator<Item = usize> + 'a {
+        self.chunks().flat_map(|(base, mut chunk)| {
+            std::iter::from_fn(move || {
+                if chunk == 0 {
+                    return None;
+                }
+                let bit = chunk.trailing_zeros() as usize;
+                chunk &= chunk - 1;
+                Some(base + bit)
+            })
+        })
+    }
+
+    /// Returns the bits of the view one atom at a time, as `(relative index, bits)`.
+    fn chunks(&self) -> impl Iterator<Item = (usize, BitArrayAtom)> + 'a {
+        let atoms = self.atoms;
+        let start = self.start;
+        let len = self.len;
+        (0..len).step_by(BIT_ARRAY_BITS_IN_ATOM).map(move |offset| {
+            let count = (len - offset).min(BIT_ARRAY_BITS_IN_ATOM);
+            (offset, read_atom_bits(atoms, start + offset, count))
+        })
+    }
+}
+
+/// A fixed-size bit array stored inline, usable in `const` contexts.
+///
+/// A `FixedBitArray<BITS, ATOMS>` holds exactly `BITS` bits. Stable Rust can not derive an
+/// array length from `BITS` in a generic parameter, so the number of backing atoms is
+/// given as well and must be `BITS.div_ceil(64)`, which is checked at compile time.
+///
+/// # Example
+///
+/// ```
+/// use bit_array_rs::FixedBitArray;
+/// const FLAGS: FixedBitArray<10, 1> = {
+///     let mut flags = FixedBitArray::new();
+///     flags.set(3);
+///     flags
+/// };
+/// assert!(FLAGS.get(3));
+/// ```
+///
+/// An atom count that does not match the bit count is rejected:
+///
+/// ```compile_fail
+/// use bit_array_rs::FixedBitArray;
+/// let flags = FixedBitArray::<10, 2>::new();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedBitArray<const BITS: usize, const ATOMS: usize> {
+    array: [BitArrayAtom; ATOMS],
+    number_of_bits_set: usize,
+}
+
+impl<const BITS: usize, const ATOMS: usize> FixedBitArray<BITS, ATOMS> {
+    /// The number of bits in the array.
+    pub const BIT_COUNT: usize = BITS;
+
+    const ATOMS_MATCH_BITS: () = assert!(
+        ATOMS == BITS.div_ceil(BIT_ARRAY_BITS_IN_ATOM),
+        "ATOMS must be BITS.div_ceil(64)"
+    );
+
+    /// Initializes a new `FixedBitArray` with all bits unset.
+    #[must_use]
+    pub const fn new() -> Self {
+        let () = Self::ATOMS_MATCH_BITS;
+        Self {
+            array: [0; ATOMS],
+            number_of_bits_set: 0,
+        }
+    }
+
+    /// Returns the total number of bits in the `FixedBitArray`.
+    #[inline]
+    #[must_use]
+    pub const fn bit_count(&self) -> usize {
+        Self::BIT_COUNT
+    }
+
+    /// Returns the number of bits that are currently set to `1`.
+    #[inline]
+    #[must_use]
+    pub const fn count_set_bits(&self) -> usize {
+        self.number_of_bits_set
+    }
+
+    /// Sets the bit at the given index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The zero-based index of the bit to set.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    pub const fn set(&mut self, index: usize) {
+        assert!(index < Self::BIT_COUNT, "Index out of bounds");
+
+        let array_index = index / BIT_ARRAY_BITS_IN_ATOM;
+        let mask = 1 << (index % BIT_ARRAY_BITS_IN_ATOM);
+        if self.array[array_index] & mask == 0 {
+            self.number_of_bits_set += 1;
+        }
+        self.array[array_index] |= mask;
+    }
+
+    /// Unsets (clears) the bit at the given index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The zero-based index of the bit to clear.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    pub const fn unset(&mut self, index: usize) {
+        assert!(index < Self::BIT_COUNT, "Index out of bounds");
+
+        let array_index = index / BIT_ARRAY_BITS_IN_ATOM;
+        let mask = 1 << (index % BIT_ARRAY_BITS_IN_ATOM);
+        if self.array[array_index] & mask != 0 {
+            self.number_of_bits_set -= 1;
+        }
+        self.array[array_index] &= !mask;
+    }
+
+    /// Returns the bit value at the specified index.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The bit index to read from.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the index is out of bounds.
+    #[must_use]
+    pub const fn get(&self, index: usize) -> bool {
+        assert!(index < Self::BIT_COUNT, "Index out of bounds");
+
+        let array_index = index / BIT_ARRAY_BITS_IN_ATOM;
+        (self.array[array_index] >> (index % BIT_ARRAY_BITS_IN_ATOM)) & 0x1 != 0
+    }
+}
+
+impl<const BITS: usize, const ATOMS: usize> Default for FixedBitArray<BITS, ATOMS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,191 @@
+/*
+ * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/bit-array-rs
+ * Licensed under the MIT License. See LICENSE in the project root for license information.
+ */
+use bit_array_rs::BitArray;
+
+const CASES: usize = 256;
+
+/// Small deterministic xorshift generator, so failures are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+/// Generates a random array with a random `bit_count` and a random density.
+fn random_array(rng: &mut Rng) -> BitArray {
+    let bit_count = 1 + rng.below(300);
+    random_array_with_len(rng, bit_count)
+}
+
+fn random_array_with_len(rng: &mut Rng, bit_count: usize) -> BitArray {
+    let density = rng.below(101);
+    let mut array = BitArray::new(bit_count);
+    for i in 0..bit_count {
+        array.set_bit(i, rng.below(100) < density);
+    }
+    array
+}
+
+fn for_each_case(seed: u64, mut check: impl FnMut(&mut Rng)) {
+    let mut rng = Rng(seed);
+    for _ in 0..CASES {
+        check(&mut rng);
+    }
+}
+
+#[test]
+fn count_set_bits_equals_recount() {
+    for_each_case(0x9E37_79B9_7F4A_7C15, |rng| {
+        let array = random_array(rng);
+        let recount = (0..array.bit_count()).filter(|&i| array.get(i)).count();
+        assert_eq!(array.count_set_bits(), recount);
+        assert_eq!(array.all_set(), recount == array.bit_count());
+    });
+}
+
+#[test]
+fn iter_set_bits_is_sorted_and_matches_get() {
+    for_each_case(0xD1B5_4A32_D192_ED03, |rng| {
+        let array = random_array(rng);
+        let indices = array.collect_set_bits();
+        assert!(indices.windows(2).all(|pair| pair[0] < pair[1]));
+
+        let expected: Vec<usize> = (0..array.bit_count()).filter(|&i| array.get(i)).collect();
+        assert_eq!(indices, expected);
+        assert_eq!(array.first_set_bit(), expected.first().copied());
+        assert_eq!(
+            array.first_unset_bit(),
+            (0..array.bit_count()).find(|&i| !array.get(i))
+        );
+    });
+}
+
+#[test]
+fn bytes_round_trip_is_identity() {
+    for_each_case(0x94D0_49BB_1331_11EB, |rng| {
+        let array = random_array(rng);
+        let bit_count = array.bit_count();
+        assert_eq!(BitArray::from_bytes(bit_count, &array.to_bytes()), array);
+        assert_eq!(
+            BitArray::from_be_bytes(bit_count, &array.to_be_bytes()),
+            array
+        );
+    });
+}
+
+#[test]
+fn xor_with_self_is_empty() {
+    for_each_case(0xBF58_476D_1CE4_E5B9, |rng| {
+        let mut array = random_array(rng);
+        let copy = array.clone();
+        array ^= &copy;
+        assert!(array.is_empty());
+        assert_eq!(array.first_set_bit(), None);
+    });
+}
+
+#[test]
+fn rle_round_trip_is_identity() {
+    for_each_case(0x2545_F491_4F6C_DD1D, |rng| {
+        let array = random_array(rng);
+        let runs = array.to_rle();
+        assert!(runs.windows(2).all(|pair| pair[0].0 != pair[1].0));
+        assert_eq!(BitArray::from_rle(&runs), array);
+    });
+}
+
+#[test]
+fn union_and_intersection_counts() {
+    for_each_case(0x1405_7B7E_F767_814F, |rng| {
+        let a = random_array(rng);
+        let b = random_array_with_len(rng, a.bit_count());
+
+        let mut union = a.clone();
+        union |= &b;
+        let mut intersection = a.clone();
+        intersection &= &b;
+
+        assert_eq!(
+            union.count_set_bits() + intersection.count_set_bits(),
+            a.count_set_bits() + b.count_set_bits()
+        );
+        assert_eq!(intersection.count_set_bits(), a.count_set_bits_masked(&b));
+    });
+}
+
+#[test]
+fn resize_grow_then_shrink_is_identity() {
+    for_each_case(0x8CB9_2BA7_2F3D_8DD7, |rng| {
+        let array = random_array(rng);
+        let fill = rng.below(2) == 0;
+        let grown = array.clone_resized(array.bit_count() + 1 + rng.below(200), fill);
+
+        let added = grown.bit_count() - array.bit_count();
+        let expected_added = if fill { added } else { 0 };
+        assert_eq!(
+            grown.count_set_bits(),
+            array.count_set_bits() + expected_added
+        );
+        assert_eq!(grown.clone_resized(array.bit_count(), !fill), array);
+    });
+}
+
+#[test]
+fn untracked_count_matches_tracked_count() {
+    for_each_case(0x6A09_E667_F3BC_C908, |rng| {
+        let mut tracked = random_array(rng);
+        let mut lazy = tracked.clone();
+        lazy.set_count_tracking(false);
+
+        for _ in 0..64 {
+            let index = rng.below(tracked.bit_count());
+            match rng.below(5) {
+                0 => {
+                    tracked.set(index);
+                    lazy.set(index);
+                }
+                1 => {
+                    tracked.unset(index);
+                    lazy.unset(index);
+                }
+                2 => {
+                    let value = rng.below(2) == 0;
+                    tracked.set_bit(index, value);
+                    lazy.set_bit(index, value);
+                }
+                3 => {
+                    let value = rng.below(2) == 0;
+                    tracked.push(value);
+                    lazy.push(value);
+                }
+                _ => {
+                    let new_bit_count = 1 + rng.below(300);
+                    let fill = rng.below(2) == 0;
+                    tracked.resize(new_bit_count, fill);
+                    lazy.resize(new_bit_count, fill);
+                }
+            }
+            assert_eq!(lazy.count_set_bits(), tracked.count_set_bits());
+            assert_eq!(lazy.all_set(), tracked.all_set());
+            assert_eq!(lazy.is_empty(), tracked.is_empty());
+            assert_eq!(lazy, tracked);
+        }
+
+        lazy.set_count_tracking(true);
+        assert_eq!(lazy.count_set_bits(), tracked.count_set_bits());
+        lazy.set(0);
+        tracked.set(0);
+        assert_eq!(lazy.count_set_bits(), tracked.count_set_bits());
+    });
+}
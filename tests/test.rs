@@ -2,7 +2,7 @@
  * Copyright (c) Peter Bjorklund. All rights reserved. https://github.com/piot/bit-array-rs
  * Licensed under the MIT License. See LICENSE in the project root for license information.
  */
-use bit_array_rs::BitArray;
+use bit_array_rs::{BitArray, FixedBitArray};
 
 #[test]
 fn basic_bit_array_functions() {
@@ -42,3 +42,2039 @@ fn bitarray_debug_output() {
 
     assert_eq!(output, EXPECTED_OUTPUT);
 }
+
+#[test]
+fn checked_new() {
+    assert!(BitArray::checked_new(0).is_none());
+
+    let array = BitArray::checked_new(16).unwrap();
+    assert_eq!(array.bit_count(), 16);
+    assert_eq!(array.count_set_bits(), 0);
+}
+
+#[test]
+fn bitwise_assign_operators() {
+    let mut a = BitArray::new(70);
+    let mut b = BitArray::new(70);
+    for i in [0, 3, 5, 64, 69] {
+        a.set(i);
+    }
+    for i in [3, 4, 64, 68] {
+        b.set(i);
+    }
+
+    let mut or_method = a.clone();
+    or_method.or_assign(&b);
+    let mut or_operator = a.clone();
+    or_operator |= &b;
+    assert_eq!(format!("{or_method}"), format!("{or_operator}"));
+    assert_eq!(or_operator.count_set_bits(), 7);
+
+    let mut and_method = a.clone();
+    and_method.and_assign(&b);
+    let mut and_operator = a.clone();
+    and_operator &= &b;
+    assert_eq!(format!("{and_method}"), format!("{and_operator}"));
+    assert_eq!(and_operator.count_set_bits(), 2);
+
+    let mut xor_method = a.clone();
+    xor_method.xor_assign(&b);
+    let mut xor_operator = a;
+    xor_operator ^= &b;
+    assert_eq!(format!("{xor_method}"), format!("{xor_operator}"));
+    assert_eq!(xor_operator.count_set_bits(), 5);
+}
+
+#[test]
+fn iter_rev() {
+    let mut array = BitArray::new(5);
+    array.set(0);
+    array.set(1);
+    array.set(3);
+
+    let mut expected = vec![true, true, false, true, false];
+    expected.reverse();
+
+    assert_eq!(array.iter_rev().count(), expected.len());
+    for (bit, expected_bit) in array.iter_rev().zip(expected) {
+        assert_eq!(bit, expected_bit);
+    }
+}
+
+#[test]
+fn density() {
+    let mut array = BitArray::new(8);
+    assert!(array.density().abs() < f64::EPSILON);
+
+    for i in 0..4 {
+        array.set(i);
+    }
+    assert!((array.density() - 0.5).abs() < f64::EPSILON);
+
+    for i in 4..8 {
+        array.set(i);
+    }
+    assert!((array.density() - 1.0).abs() < f64::EPSILON);
+}
+
+#[test]
+fn merge_with_custom_operation() {
+    let mut a = BitArray::new(4);
+    let mut b = BitArray::new(4);
+    a.set(0);
+    a.set(1);
+    b.set(1);
+    b.set(2);
+
+    let mut expected = BitArray::new(4);
+    for i in 0..4 {
+        expected.set_bit(i, !(a.get(i) && b.get(i)));
+    }
+
+    a.merge_with(&b, |left, right| !(left && right));
+
+    assert_eq!(format!("{a}"), "1011");
+    assert_eq!(format!("{a}"), format!("{expected}"));
+    assert_eq!(a.count_set_bits(), 3);
+}
+
+#[test]
+fn from_atoms_masks_padding_bits() {
+    let array = BitArray::from_atoms(10, &[0xFFFF_0000_0000_0000 | 0b101]);
+
+    assert_eq!(array.count_set_bits(), 2);
+    assert_eq!(array.first_set_bit(), Some(0));
+    assert!(!array.all_set());
+    assert_eq!(format!("{array}"), "1010000000");
+
+    let full = BitArray::from_atoms(10, &[u64::MAX]);
+    assert_eq!(full.count_set_bits(), 10);
+    assert!(full.all_set());
+}
+
+#[test]
+fn bitarray_display_alternate_grouping() {
+    let mut bit_array = BitArray::new(16);
+    bit_array.set(3);
+    bit_array.set(7);
+    bit_array.set(9);
+    bit_array.set(15);
+
+    assert_eq!(format!("{bit_array}"), "0001000101000001");
+    assert_eq!(format!("{bit_array:#}"), "00010001 01000001");
+}
+
+#[test]
+fn reset_atom() {
+    let mut array = BitArray::new(150);
+    for i in [1, 63, 64, 100, 127, 128, 149] {
+        array.set(i);
+    }
+    assert_eq!(array.atom_len(), 3);
+
+    array.reset_atom(1);
+
+    assert_eq!(array.count_set_bits(), 4);
+    assert!(array.get(1));
+    assert!(array.get(63));
+    assert!(!array.get(64));
+    assert!(!array.get(100));
+    assert!(!array.get(127));
+    assert!(array.get(128));
+    assert!(array.get(149));
+
+    array.reset_atom(2);
+    assert_eq!(array.count_set_bits(), 2);
+}
+
+#[test]
+fn set_bit_keeps_count_in_sync() {
+    let mut array = BitArray::new(100);
+    for i in (0..100).step_by(3) {
+        array.set_bit(i, true);
+    }
+    array.set_bit(3, true);
+    for i in (0..100).step_by(6) {
+        array.set_bit(i, false);
+    }
+    array.set_bit(1, false);
+
+    let recount = (0..100).filter(|&i| array.get(i)).count();
+    assert_eq!(recount, 17);
+    assert_eq!(array.count_set_bits(), recount);
+}
+
+#[test]
+fn from_indices_ignores_duplicates() {
+    let array = BitArray::from_indices(70, &[1, 5, 5, 69, 1, 64]);
+
+    assert_eq!(array.count_set_bits(), 4);
+    assert!(array.get(1));
+    assert!(array.get(5));
+    assert!(array.get(64));
+    assert!(array.get(69));
+    assert!(!array.get(0));
+}
+
+#[test]
+#[should_panic(expected = "bit index 8 out of bounds for BitArray of 8 bits")]
+fn from_indices_out_of_bounds() {
+    let _ = BitArray::from_indices(8, &[8]);
+}
+
+#[test]
+fn get_ref_returns_shared_static() {
+    let array = BitArray::from_indices(8, &[1, 2]);
+
+    assert!(std::ptr::eq(array.get_ref(1), array.get_ref(2)));
+    assert!(std::ptr::eq(array.get_ref(0), array.get_ref(7)));
+    assert!(!std::ptr::eq(array.get_ref(0), array.get_ref(1)));
+    assert!(std::ptr::eq(&array[1], array.get_ref(2)));
+}
+
+#[test]
+fn count_set_bits_masked() {
+    let mut dense = BitArray::new(100);
+    for i in 0..100 {
+        if i % 10 != 0 {
+            dense.set(i);
+        }
+    }
+    let mask = BitArray::from_indices(100, &[0, 1, 10, 55, 64, 99]);
+
+    assert_eq!(dense.count_set_bits_masked(&mask), 4);
+    assert_eq!(mask.count_set_bits_masked(&dense), 4);
+}
+
+#[test]
+fn to_rle() {
+    let array = BitArray::from_indices(12, &[2, 3, 4, 8, 11]);
+
+    let runs = array.to_rle();
+
+    assert_eq!(
+        runs,
+        vec![
+            (false, 2),
+            (true, 3),
+            (false, 3),
+            (true, 1),
+            (false, 2),
+            (true, 1)
+        ]
+    );
+    assert_eq!(runs.iter().map(|&(_, length)| length).sum::<usize>(), 12);
+
+    let decoded = BitArray::from_rle(&runs);
+    assert_eq!(format!("{decoded}"), format!("{array}"));
+}
+
+#[test]
+fn from_rle_round_trip() {
+    let mut array = BitArray::new(200);
+    for i in (10..140).chain(150..151).chain(190..200) {
+        array.set(i);
+    }
+
+    let decoded = BitArray::from_rle(&array.to_rle());
+
+    assert_eq!(decoded.bit_count(), 200);
+    assert_eq!(decoded.count_set_bits(), array.count_set_bits());
+    assert_eq!(format!("{decoded}"), format!("{array}"));
+}
+
+#[test]
+fn from_rle_allows_zero_length_runs() {
+    let array = BitArray::from_rle(&[(true, 0), (false, 3), (true, 2), (false, 0)]);
+
+    assert_eq!(array.bit_count(), 5);
+    assert_eq!(array.count_set_bits(), 2);
+    assert_eq!(format!("{array}"), "00011");
+}
+
+#[test]
+fn first_set_and_unset_bit_match_bit_scan() {
+    let patterns: [&[usize]; 5] = [&[], &[0], &[63, 64], &[5, 70, 129], &[199]];
+    for indices in patterns {
+        let mut array = BitArray::from_indices(200, indices);
+        for _ in 0..2 {
+            let expected_set = (0..200).find(|&i| array.get(i));
+            let expected_unset = (0..200).find(|&i| !array.get(i));
+            assert_eq!(array.first_set_bit(), expected_set);
+            assert_eq!(array.first_unset_bit(), expected_unset);
+            for i in 0..100 {
+                array.set_bit(i, !array.get(i));
+            }
+        }
+    }
+}
+
+#[test]
+fn first_unset_bit_ignores_padding() {
+    let mut array = BitArray::new(70);
+    for i in 0..70 {
+        array.set(i);
+    }
+    assert_eq!(array.first_unset_bit(), None);
+
+    array.unset(69);
+    assert_eq!(array.first_unset_bit(), Some(69));
+}
+
+#[test]
+fn clear_first_set_drains_in_order() {
+    let indices = [3, 17, 63, 64, 100, 129];
+    let mut array = BitArray::from_indices(130, &[100, 3, 129, 64, 17, 63]);
+
+    let mut drained = Vec::new();
+    while let Some(index) = array.clear_first_set() {
+        drained.push(index);
+    }
+
+    assert_eq!(drained, indices);
+    assert_eq!(array.count_set_bits(), 0);
+    assert_eq!(array.first_set_bit(), None);
+}
+
+#[test]
+fn ord_in_btree_set() {
+    use std::collections::BTreeSet;
+
+    let small = BitArray::from_indices(8, &[7]);
+    let low = BitArray::from_indices(100, &[1]);
+    let high = BitArray::from_indices(100, &[2]);
+    let second_atom = BitArray::from_indices(100, &[0, 70]);
+    let empty = BitArray::new(100);
+
+    let set: BTreeSet<BitArray> = [
+        second_atom.clone(),
+        high.clone(),
+        empty.clone(),
+        low.clone(),
+        small.clone(),
+        low.clone(),
+    ]
+    .into_iter()
+    .collect();
+
+    let ordered: Vec<BitArray> = set.into_iter().collect();
+    assert_eq!(ordered, vec![small, empty, second_atom, low, high]);
+}
+
+#[test]
+fn ord_and_eq_ignore_padding_bits() {
+    use std::cmp::Ordering;
+
+    let clean = BitArray::new(10);
+    let mut padded = clean.clone();
+    padded.atoms_mut()[0] |= 1 << 20;
+
+    assert_eq!(padded.cmp(&clean), Ordering::Equal);
+    assert_eq!(clean.cmp(&padded), Ordering::Equal);
+    assert_eq!(padded, clean);
+
+    padded.set(3);
+    assert_eq!(padded.cmp(&clean), Ordering::Greater);
+}
+
+#[test]
+fn partial_eq() {
+    let a = BitArray::from_indices(10, &[1, 9]);
+    let b = BitArray::from_indices(10, &[9, 1]);
+    let c = BitArray::from_indices(11, &[1, 9]);
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_ne!(a, BitArray::new(10));
+}
+
+#[test]
+fn append_many_small_arrays() {
+    let mut appended = BitArray::new(1);
+    appended.set(0);
+    let mut expected = vec![true];
+
+    for n in 1..40 {
+        let length = n % 7 + 1;
+        let mut part = BitArray::new(length);
+        for i in 0..length {
+            let value = (n + i) % 3 == 0;
+            part.set_bit(i, value);
+            expected.push(value);
+        }
+        appended.append(&part);
+    }
+
+    assert_eq!(appended.bit_count(), expected.len());
+    assert_eq!(
+        appended.count_set_bits(),
+        expected.iter().filter(|&&value| value).count()
+    );
+    for (i, &value) in expected.iter().enumerate() {
+        assert_eq!(appended.get(i), value, "bit {i}");
+    }
+}
+
+#[test]
+fn append_across_atom_boundary() {
+    let mut a = BitArray::from_indices(60, &[0, 59]);
+    let b = BitArray::from_indices(70, &[0, 3, 4, 69]);
+
+    a.append(&b);
+
+    assert_eq!(a, BitArray::from_indices(130, &[0, 59, 60, 63, 64, 129]));
+}
+
+#[test]
+fn set_atom() {
+    let mut array = BitArray::new(140);
+    array.set(0);
+    array.set(64);
+
+    array.set_atom(1, 0xF0F0);
+    assert_eq!(array.count_set_bits(), 9);
+    assert!(!array.get(64));
+    assert!(array.get(68));
+
+    array.set_atom(2, u64::MAX);
+    assert_eq!(array.count_set_bits(), 21);
+    assert!(array.get(139));
+
+    array.set_atom(2, 0b1);
+    assert_eq!(array.count_set_bits(), 10);
+    assert!(array.get(128));
+    assert!(!array.get(139));
+}
+
+#[test]
+fn with_all_set() {
+    let array = BitArray::with_all_set(100);
+
+    assert!(array.all_set());
+    assert_eq!(array.count_set_bits(), 100);
+    assert_eq!(array.first_unset_bit(), None);
+    assert!(array.get(99));
+}
+
+#[test]
+fn clone_resized_grows_and_shrinks() {
+    let original = BitArray::from_indices(70, &[0, 5, 64, 69]);
+
+    let grown = original.clone_resized(150, true);
+    assert_eq!(grown.bit_count(), 150);
+    assert_eq!(grown.count_set_bits(), 84);
+    assert!(grown.get(69));
+    assert!(!grown.get(68));
+    assert!(grown.get(70));
+    assert!(grown.get(149));
+
+    let grown_unset = original.clone_resized(150, false);
+    assert_eq!(grown_unset.count_set_bits(), 4);
+    assert_eq!(grown_unset.first_set_bit(), Some(0));
+
+    let shrunk = original.clone_resized(6, true);
+    assert_eq!(shrunk, BitArray::from_indices(6, &[0, 5]));
+    assert_eq!(shrunk.count_set_bits(), 2);
+
+    assert_eq!(original, BitArray::from_indices(70, &[0, 5, 64, 69]));
+}
+
+#[test]
+fn atoms_mut_and_recount() {
+    let mut array = BitArray::new(100);
+    array.atoms_mut()[1] = 0b1011;
+    assert_eq!(array.count_set_bits(), 0);
+
+    array.recount_set_bits();
+    assert_eq!(array.count_set_bits(), 3);
+    assert_eq!(array.atoms(), &[0, 0b1011]);
+    assert!(array.get(67));
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "out of sync")]
+fn unset_detects_count_desync() {
+    let mut array = BitArray::new(8);
+    array.atoms_mut()[0] = 1;
+    array.unset(0);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "out of sync")]
+fn set_bit_detects_count_desync() {
+    let mut array = BitArray::new(8);
+    array.atoms_mut()[0] = 1;
+    array.set_bit(0, false);
+}
+
+#[test]
+fn to_bytes() {
+    let array = BitArray::from_indices(20, &[3, 7, 9, 15, 19]);
+
+    assert_eq!(array.to_bytes(), vec![0x88, 0x82, 0x08]);
+}
+
+#[test]
+fn hex_dump() {
+    let mut array = BitArray::from_indices(40 * 8, &[3, 7, 9, 15, 128, 257]);
+    array.set(16 * 8 + 7);
+
+    let dump = array.hex_dump();
+    let lines: Vec<&str> = dump.lines().collect();
+
+    assert_eq!(lines.len(), 3);
+    assert_eq!(
+        lines[0],
+        "0000: 88 82 00 00 00 00 00 00 00 00 00 00 00 00 00 00"
+    );
+    assert!(lines[1].starts_with("0010: 81 00"));
+    assert_eq!(lines[2], "0020: 02 00 00 00 00 00 00 00");
+}
+
+#[test]
+fn zeros_and_ones() {
+    let zeros = BitArray::zeros(8);
+    assert!(zeros.is_empty());
+    assert!(!zeros.is_full());
+
+    let ones = BitArray::ones(8);
+    assert!(ones.is_full());
+    assert!(!ones.is_empty());
+    assert_eq!(ones.count_set_bits(), 8);
+}
+
+#[test]
+fn iter_set_bits_from_both_ends() {
+    let array = BitArray::from_indices(130, &[0, 7, 63, 64, 100, 129]);
+
+    assert_eq!(
+        array.iter_set_bits().collect::<Vec<_>>(),
+        vec![0, 7, 63, 64, 100, 129]
+    );
+    assert_eq!(array.iter_set_bits().next_back(), Some(129));
+
+    let mut iter = array.iter_set_bits();
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(iter.next_back(), Some(129));
+    assert_eq!(iter.next(), Some(7));
+    assert_eq!(iter.next_back(), Some(100));
+    assert_eq!(iter.next_back(), Some(64));
+    assert_eq!(iter.next(), Some(63));
+    assert_eq!(iter.next(), None);
+    assert_eq!(iter.next_back(), None);
+}
+
+#[test]
+fn iter_is_exact_size() {
+    let array = BitArray::from_indices(70, &[1, 69]);
+
+    let mut iter = array.iter();
+    assert_eq!(iter.len(), 70);
+    assert_eq!(iter.size_hint(), (70, Some(70)));
+
+    assert_eq!(iter.next(), Some(false));
+    assert_eq!(iter.next(), Some(true));
+    assert_eq!(iter.next_back(), Some(true));
+    assert_eq!(iter.len(), 67);
+    assert_eq!(iter.size_hint(), (67, Some(67)));
+
+    let collected: Vec<bool> = (&array).into_iter().collect();
+    assert_eq!(collected.len(), 70);
+    assert_eq!(collected.iter().filter(|&&bit| bit).count(), 2);
+}
+
+#[test]
+fn resize_fills_former_padding_bits() {
+    let mut array = BitArray::with_all_set(10);
+
+    array.resize(20, true);
+
+    assert_eq!(array.bit_count(), 20);
+    assert!(array.all_set());
+    assert!((10..20).all(|i| array.get(i)));
+    assert_eq!(array.atoms(), &[(1 << 20) - 1]);
+
+    array.resize(70, true);
+    assert_eq!(array.count_set_bits(), 70);
+    assert_eq!(array.atoms()[1], (1 << 6) - 1);
+}
+
+#[test]
+fn count_set_runs() {
+    assert_eq!(BitArray::new(100).count_set_runs(), 0);
+    assert_eq!(BitArray::with_all_set(100).count_set_runs(), 1);
+    assert_eq!(BitArray::from_indices(4, &[0, 2]).count_set_runs(), 2);
+
+    let mut array = BitArray::new(200);
+    for i in (60..70).chain(127..128).chain(128..130).chain(199..200) {
+        array.set(i);
+    }
+    assert_eq!(array.count_set_runs(), 3);
+}
+
+#[test]
+fn longest_set_run() {
+    assert_eq!(BitArray::new(100).longest_set_run(), None);
+
+    let mut array = BitArray::new(200);
+    for i in (3..6).chain(60..70) {
+        array.set(i);
+    }
+    assert_eq!(array.longest_set_run(), Some((60, 10)));
+
+    let tie = BitArray::from_indices(20, &[2, 3, 4, 10, 11, 12]);
+    assert_eq!(tie.longest_set_run(), Some((2, 3)));
+
+    assert_eq!(BitArray::with_all_set(70).longest_set_run(), Some((0, 70)));
+}
+
+#[test]
+fn longest_unset_run() {
+    assert_eq!(BitArray::with_all_set(70).longest_unset_run(), None);
+    assert_eq!(BitArray::new(70).longest_unset_run(), Some((0, 70)));
+
+    let mut array = BitArray::with_all_set(100);
+    for i in (10..15).chain(95..100) {
+        array.unset(i);
+    }
+    assert_eq!(array.longest_unset_run(), Some((10, 5)));
+
+    array.unset(94);
+    assert_eq!(array.longest_unset_run(), Some((94, 6)));
+}
+
+#[test]
+fn find_unset_run_first_fit() {
+    let mut array = BitArray::with_all_set(200);
+    for i in (5..8).chain(60..75).chain(150..200) {
+        array.unset(i);
+    }
+
+    assert_eq!(array.find_unset_run(0), Some(0));
+    assert_eq!(array.find_unset_run(3), Some(5));
+    assert_eq!(array.find_unset_run(4), Some(60));
+    assert_eq!(array.find_unset_run(15), Some(60));
+    assert_eq!(array.find_unset_run(16), Some(150));
+    assert_eq!(array.find_unset_run(50), Some(150));
+    assert_eq!(array.find_unset_run(51), None);
+}
+
+#[test]
+fn claim_unset_run() {
+    let mut array = BitArray::new(100);
+    array.set(10);
+
+    let first = array.claim_unset_run(8).unwrap();
+    let second = array.claim_unset_run(8).unwrap();
+
+    assert_eq!(first, 0);
+    assert_eq!(second, 11);
+    assert_eq!(array.count_set_bits(), 17);
+    assert!((0..8).chain(10..19).all(|i| array.get(i)));
+
+    assert_eq!(array.longest_unset_run(), Some((19, 81)));
+    assert_eq!(array.claim_unset_run(82), None);
+    assert_eq!(array.count_set_bits(), 17);
+}
+
+#[test]
+fn set_bit_count_within_atom() {
+    let mut array = BitArray::with_all_set(60);
+    let atoms_before = array.atoms().as_ptr();
+
+    array.set_bit_count(50);
+
+    assert_eq!(array.bit_count(), 50);
+    assert_eq!(array.count_set_bits(), 50);
+    assert!(array.all_set());
+    assert_eq!(array.atoms(), &[(1 << 50) - 1]);
+    assert_eq!(array.atoms().as_ptr(), atoms_before);
+
+    array.set_bit_count(55);
+    assert_eq!(array.count_set_bits(), 50);
+    assert!(!array.get(50));
+    assert_eq!(array.first_unset_bit(), Some(50));
+}
+
+#[test]
+fn to_braille() {
+    let array = BitArray::from_indices(8, &[0, 3]);
+    assert_eq!(array.to_braille(), "\u{2809}");
+
+    let mut array = BitArray::with_all_set(12);
+    array.unset(11);
+    assert_eq!(array.to_braille(), "\u{28FF}\u{2807}");
+}
+
+#[test]
+fn bitwise_with_u32_pattern() {
+    let mut array = BitArray::new(70);
+
+    array.or_with_u32(0x0F0F_0F0F);
+
+    assert_eq!(array.count_set_bits(), 32 + 4);
+    for i in 0..70 {
+        assert_eq!(array.get(i), i % 8 < 4, "bit {i}");
+    }
+    assert_eq!(array.atoms()[1], 0x0F);
+
+    array.xor_with_u32(0xFFFF_FFFF);
+    assert_eq!(array.count_set_bits(), 34);
+    assert!(!array.get(0));
+    assert!(array.get(4));
+    assert_eq!(array.atoms()[1], 0x30);
+
+    array.and_with_u32(0x0000_00F0);
+    assert_eq!(
+        array.iter_set_bits().collect::<Vec<_>>(),
+        vec![4, 5, 6, 7, 36, 37, 38, 39, 68, 69]
+    );
+    assert_eq!(array.count_set_bits(), 10);
+}
+
+#[test]
+fn has_single_bit() {
+    let mut array = BitArray::new(100);
+    assert!(!array.has_single_bit());
+
+    array.set(70);
+    assert!(array.has_single_bit());
+
+    array.set(3);
+    assert!(!array.has_single_bit());
+}
+
+#[test]
+fn only_bit() {
+    let mut array = BitArray::new(100);
+    assert_eq!(array.only_bit(), None);
+
+    array.set(70);
+    assert_eq!(array.only_bit(), Some(70));
+
+    array.set(3);
+    assert_eq!(array.only_bit(), None);
+}
+
+#[test]
+fn interleave() {
+    let a = BitArray::from_indices(3, &[0, 1]);
+    let b = BitArray::from_indices(3, &[2]);
+
+    let result = a.interleave(&b);
+
+    assert_eq!(result.bit_count(), 6);
+    assert_eq!(format!("{result}"), "101001");
+    assert_eq!(
+        result.count_set_bits(),
+        a.count_set_bits() + b.count_set_bits()
+    );
+}
+
+#[test]
+fn deinterleave_round_trip() {
+    let a = BitArray::from_indices(70, &[0, 1, 33, 69]);
+    let b = BitArray::from_indices(70, &[2, 64, 65]);
+
+    let (even, odd) = a.interleave(&b).deinterleave();
+
+    assert_eq!(even, a);
+    assert_eq!(odd, b);
+}
+
+#[test]
+fn deinterleave_odd_bit_count() {
+    let array = BitArray::from_indices(5, &[1, 4]);
+
+    let (even, odd) = array.deinterleave();
+
+    assert_eq!(format!("{even}"), "001");
+    assert_eq!(format!("{odd}"), "10");
+}
+
+#[test]
+fn reserve_and_push() {
+    let mut array = BitArray::new(3);
+    array.set(1);
+    array.reserve(1000);
+
+    for i in 0..1000 {
+        array.push(i % 7 == 0);
+    }
+
+    assert_eq!(array.bit_count(), 1003);
+    assert_eq!(array.atom_len(), 16);
+    assert_eq!(array.count_set_bits(), 1 + 143);
+    assert!(array.get(1));
+    assert!(array.get(3));
+    assert!(!array.get(4));
+    assert!(array.get(3 + 994));
+}
+
+#[test]
+fn from_fn() {
+    let array = BitArray::from_fn(100, |i| i % 3 == 0);
+
+    assert_eq!(array.bit_count(), 100);
+    assert_eq!(array.atom_len(), 2);
+    assert_eq!(array.count_set_bits(), 34);
+    assert!(array.iter_set_bits().all(|i| i % 3 == 0));
+    assert_eq!(array.iter_set_bits().next_back(), Some(99));
+}
+
+#[test]
+fn diff() {
+    let before = BitArray::from_indices(100, &[1, 5, 64, 99]);
+    let after = BitArray::from_indices(100, &[1, 6, 65, 70, 99]);
+
+    let (newly_set, newly_cleared) = before.diff(&after);
+
+    assert_eq!(newly_set, vec![6, 65, 70]);
+    assert_eq!(newly_cleared, vec![5, 64]);
+    assert_eq!(before.diff(&before), (vec![], vec![]));
+}
+
+#[test]
+fn atom_count_ones() {
+    let array = BitArray::from_fn(150, |i| i % 5 == 0 || i > 140);
+
+    assert_eq!(array.atom_count_ones(0), 13);
+    assert_eq!(array.atom_count_ones(2), 12);
+    let total: u32 = (0..array.atom_len())
+        .map(|atom_index| array.atom_count_ones(atom_index))
+        .sum();
+    assert_eq!(total as usize, array.count_set_bits());
+}
+
+#[test]
+fn copy_bits_from_aligned() {
+    let src = BitArray::from_fn(200, |i| i % 3 == 0);
+    let mut dst = BitArray::with_all_set(200);
+
+    dst.copy_bits_from(&src, 64, 0, 128);
+
+    assert_eq!(
+        dst.count_set_bits(),
+        (64..192).filter(|i| i % 3 == 0).count() + 72
+    );
+    for i in 0..128 {
+        assert_eq!(dst.get(i), (i + 64) % 3 == 0, "bit {i}");
+    }
+    assert!((128..200).all(|i| dst.get(i)));
+}
+
+#[test]
+fn copy_bits_from_unaligned() {
+    let src = BitArray::from_fn(150, |i| i % 5 < 2);
+    let mut dst = BitArray::new(170);
+
+    dst.copy_bits_from(&src, 3, 17, 140);
+
+    for i in 0..170 {
+        let expected = (17..157).contains(&i) && (i - 17 + 3) % 5 < 2;
+        assert_eq!(dst.get(i), expected, "bit {i}");
+    }
+    assert_eq!(
+        dst.count_set_bits(),
+        (0..170).filter(|&i| dst.get(i)).count()
+    );
+}
+
+#[test]
+fn copy_bits_from_overlapping_clone() {
+    let mut array = BitArray::from_fn(100, |i| i < 50 && i % 2 == 0);
+    let snapshot = array.clone();
+
+    array.copy_bits_from(&snapshot, 0, 10, 80);
+
+    for i in 0..100 {
+        let expected = if i < 10 {
+            snapshot.get(i)
+        } else {
+            snapshot.get(i - 10)
+        };
+        assert_eq!(array.get(i), expected, "bit {i}");
+    }
+    assert_eq!(array.count_set_bits(), 30);
+}
+
+#[test]
+#[should_panic(expected = "Destination range out of bounds")]
+fn copy_bits_from_out_of_bounds() {
+    let src = BitArray::new(100);
+    let mut dst = BitArray::new(50);
+    dst.copy_bits_from(&src, 0, 10, 41);
+}
+
+#[test]
+fn fill_pattern() {
+    let mut array = BitArray::new(101);
+
+    array.fill_pattern(0xAAAA_AAAA);
+
+    assert_eq!(array.count_set_bits(), 50);
+    for i in 0..101 {
+        assert_eq!(array.get(i), i % 2 == 1, "bit {i}");
+    }
+    assert_eq!(array.atoms()[1] >> 37, 0);
+}
+
+#[test]
+fn bitarray_debug_spacing() {
+    let mut array = BitArray::new(8);
+    array.set(0);
+    assert_eq!(format!("{array:?}"), "10000000");
+
+    let mut array = BitArray::new(9);
+    array.set(8);
+    assert_eq!(format!("{array:?}"), "00000000 1");
+
+    let array = BitArray::with_all_set(16);
+    assert_eq!(format!("{array:?}"), "11111111 11111111");
+
+    assert_eq!(format!("{:?}", BitArray::new(1)), "0");
+}
+
+#[test]
+fn enum_flags() {
+    #[derive(Clone, Copy)]
+    #[repr(usize)]
+    enum Flag {
+        Visible = 0,
+        Dirty = 3,
+        Locked = 9,
+    }
+
+    impl From<Flag> for usize {
+        fn from(flag: Flag) -> Self {
+            flag as Self
+        }
+    }
+
+    let mut array = BitArray::new(10);
+    array.set_enum(Flag::Dirty);
+    array.set_enum(Flag::Locked);
+
+    assert!(!array.get_enum(Flag::Visible));
+    assert!(array.get_enum(Flag::Dirty));
+    assert!(array.get_enum(Flag::Locked));
+    assert!(array.get(3));
+    assert_eq!(array.count_set_bits(), 2);
+}
+
+#[test]
+fn from_u32_and_u64() {
+    let array = BitArray::from_u32(0b1011);
+    assert_eq!(array.bit_count(), 32);
+    assert_eq!(array.collect_set_bits(), vec![0, 1, 3]);
+    assert_eq!(array.count_set_bits(), 3);
+
+    let array = BitArray::from_u64(1 << 63 | 1);
+    assert_eq!(array.bit_count(), 64);
+    assert_eq!(array.collect_set_bits(), vec![0, 63]);
+    assert_eq!(BitArray::from_u32(u32::MAX).count_set_bits(), 32);
+}
+
+#[test]
+fn as_u32_and_u64() {
+    let array = BitArray::from_indices(10, &[0, 3, 9]);
+    assert_eq!(array.as_u32(), Some(0b10_0000_1001));
+    assert_eq!(array.as_u64(), Some(0b10_0000_1001));
+
+    let array = BitArray::from_indices(40, &[0, 39]);
+    assert_eq!(array.as_u32(), None);
+    assert_eq!(array.as_u64(), Some(1 << 39 | 1));
+
+    assert_eq!(BitArray::new(65).as_u64(), None);
+    assert_eq!(BitArray::from_u32(0xDEAD_BEEF).as_u32(), Some(0xDEAD_BEEF));
+}
+
+#[test]
+fn iter_set_bits_dense_atoms() {
+    let array = BitArray::with_all_set(64);
+    assert_eq!(array.collect_set_bits(), (0..64).collect::<Vec<_>>());
+
+    let array = BitArray::with_all_set(200);
+    assert_eq!(array.collect_set_bits(), (0..200).collect::<Vec<_>>());
+    assert_eq!(
+        array.iter_set_bits().rev().collect::<Vec<_>>(),
+        (0..200).rev().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn iter_set_bits_matches_get() {
+    let array = BitArray::from_fn(300, |i| (i * 7) % 11 < 4);
+    let expected: Vec<usize> = (0..300).filter(|&i| array.get(i)).collect();
+
+    assert_eq!(array.collect_set_bits(), expected);
+
+    let mut iter = array.iter_set_bits();
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    loop {
+        match (iter.next(), iter.next_back()) {
+            (Some(a), Some(b)) => {
+                front.push(a);
+                back.push(b);
+            }
+            (Some(a), None) => front.push(a),
+            (None, Some(b)) => back.push(b),
+            (None, None) => break,
+        }
+    }
+    back.reverse();
+    front.extend(back);
+    assert_eq!(front, expected);
+}
+
+#[test]
+fn bit_slice_matches_allocated_slice() {
+    let array = BitArray::from_fn(300, |i| (i * 13) % 7 < 3);
+
+    for (start, end) in [(0, 300), (0, 64), (3, 70), (64, 128), (65, 299), (100, 101)] {
+        let view = array.view(start, end);
+        let slice = array.slice(start, end);
+
+        assert_eq!(view.len(), slice.bit_count());
+        assert_eq!(view.count_set_bits(), slice.count_set_bits());
+        assert_eq!(
+            view.iter_set_bits().collect::<Vec<_>>(),
+            slice.collect_set_bits()
+        );
+        for i in 0..view.len() {
+            assert_eq!(view.get(i), slice.get(i));
+            assert_eq!(view.get(i), array.get(start + i));
+        }
+    }
+
+    let empty = array.view(10, 10);
+    assert!(empty.is_empty());
+    assert_eq!(empty.count_set_bits(), 0);
+    assert_eq!(empty.iter_set_bits().next(), None);
+}
+
+#[test]
+fn checked_new_rejects_unallocatable_size() {
+    assert!(BitArray::checked_new(usize::MAX).is_none());
+}
+
+#[test]
+#[should_panic(expected = "could not allocate BitArray")]
+fn new_panics_on_unallocatable_size() {
+    let _ = BitArray::new(usize::MAX);
+}
+
+#[test]
+fn bytes_round_trip() {
+    let array = BitArray::from_fn(77, |i| (i * 5) % 9 < 4);
+
+    let le = array.to_bytes();
+    let be = array.to_be_bytes();
+    assert_eq!(le.len(), 10);
+    assert_eq!(be.len(), 10);
+
+    assert_eq!(BitArray::from_bytes(77, &le), array);
+    assert_eq!(BitArray::from_be_bytes(77, &be), array);
+}
+
+#[test]
+fn be_bytes_bit_order() {
+    let array = BitArray::from_indices(12, &[0, 1, 9]);
+
+    assert_eq!(array.to_bytes(), vec![0b0000_0011, 0b0000_0010]);
+    assert_eq!(array.to_be_bytes(), vec![0b1100_0000, 0b0100_0000]);
+    assert_ne!(array.to_bytes(), array.to_be_bytes());
+}
+
+#[test]
+fn from_bytes_ignores_unused_bits() {
+    let array = BitArray::from_bytes(4, &[0xFF]);
+
+    assert_eq!(array.count_set_bits(), 4);
+    assert!(array.all_set());
+}
+
+#[test]
+fn count_set_bits_in_atoms() {
+    let array = BitArray::from_fn(1000, |i| (i * 3) % 7 < 2);
+    assert_eq!(array.atom_len(), 16);
+
+    let parts = [(0, 5), (5, 6), (6, 6), (6, 16)];
+    let total: usize = parts
+        .iter()
+        .map(|&(start, end)| array.count_set_bits_in_atoms(start, end))
+        .sum();
+
+    assert_eq!(total, array.count_set_bits());
+    assert_eq!(array.count_set_bits_in_atoms(0, 16), array.count_set_bits());
+    assert_eq!(array.count_set_bits_in_atoms(3, 3), 0);
+}
+
+#[test]
+fn resize_matches_bit_by_bit_reference() {
+    let pattern = |i: usize| (i * 11) % 13 < 5;
+    for old_bit_count in [1, 10, 63, 64, 65, 130] {
+        for new_bit_count in [1, 9, 64, 65, 100, 128, 129, 300] {
+            for fill in [false, true] {
+                let mut array = BitArray::from_fn(old_bit_count, pattern);
+                array.resize(new_bit_count, fill);
+
+                let expected = BitArray::from_fn(new_bit_count, |i| {
+                    if i < old_bit_count {
+                        pattern(i)
+                    } else {
+                        fill
+                    }
+                });
+                assert_eq!(array, expected, "{old_bit_count} -> {new_bit_count} {fill}");
+                assert_eq!(array.count_set_bits(), expected.count_set_bits());
+                assert_eq!(array.atom_len(), new_bit_count.div_ceil(64));
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "BitArray length mismatch: left has 10 bits, right has 20 bits")]
+fn binary_operation_length_mismatch() {
+    let mut a = BitArray::new(10);
+    let b = BitArray::new(20);
+    a.or_assign(&b);
+}
+
+#[test]
+fn bit_array_error_display() {
+    let err = bit_array_rs::BitArrayError::LengthMismatch { left: 3, right: 4 };
+    assert_eq!(
+        err.to_string(),
+        "BitArray length mismatch: left has 3 bits, right has 4 bits"
+    );
+}
+
+#[test]
+fn fixed_bit_array_in_const_context() {
+    const FLAGS: FixedBitArray<128, 2> = {
+        let mut flags = FixedBitArray::new();
+        flags.set(3);
+        flags.set(100);
+        flags.set(127);
+        flags.unset(127);
+        flags
+    };
+    const COUNT: usize = FLAGS.count_set_bits();
+    const {
+        assert!(FLAGS.get(100));
+        assert!(!FLAGS.get(4));
+    }
+
+    assert_eq!(COUNT, 2);
+    assert_eq!(FLAGS.bit_count(), 128);
+    assert_eq!(FixedBitArray::<128, 2>::BIT_COUNT, 128);
+    assert_eq!(FixedBitArray::<128, 2>::default().count_set_bits(), 0);
+}
+
+#[test]
+fn fixed_bit_array_with_partial_atom() {
+    let mut flags = FixedBitArray::<10, 1>::new();
+    assert_eq!(flags.bit_count(), 10);
+    assert_eq!(FixedBitArray::<10, 1>::BIT_COUNT, 10);
+    flags.set(9);
+    flags.unset(0);
+    assert!(flags.get(9));
+    assert_eq!(flags.count_set_bits(), 1);
+    assert_eq!(FixedBitArray::<65, 2>::new().bit_count(), 65);
+}
+
+#[test]
+#[should_panic(expected = "Index out of bounds")]
+fn fixed_bit_array_get_out_of_bounds() {
+    let _ = FixedBitArray::<10, 1>::new().get(10);
+}
+
+#[test]
+#[should_panic(expected = "Index out of bounds")]
+fn fixed_bit_array_set_in_padding() {
+    FixedBitArray::<10, 1>::new().set(63);
+}
+
+#[test]
+fn normalize_after_raw_atom_writes() {
+    let mut array = BitArray::new(10);
+    array.atoms_mut()[0] = u64::MAX;
+
+    array.normalize();
+
+    assert_eq!(array.count_set_bits(), 10);
+    assert!(array.all_set());
+    assert_eq!(array.first_unset_bit(), None);
+    assert_eq!(array.atoms(), &[(1 << 10) - 1]);
+}
+
+#[test]
+fn extended_binary_operations() {
+    let short = BitArray::from_indices(10, &[0, 3, 9]);
+    let long = BitArray::from_indices(40, &[3, 5, 20, 39]);
+
+    let xor = short.xor_extended(&long);
+    assert_eq!(xor.bit_count(), 40);
+    assert_eq!(xor.collect_set_bits(), vec![0, 5, 9, 20, 39]);
+    assert_eq!(long.xor_extended(&short), xor);
+
+    let and = short.and_extended(&long);
+    assert_eq!(and.bit_count(), 40);
+    assert_eq!(and.collect_set_bits(), vec![3]);
+
+    let or = long.or_extended(&short);
+    assert_eq!(or.bit_count(), 40);
+    assert_eq!(or.collect_set_bits(), vec![0, 3, 5, 9, 20, 39]);
+    assert_eq!(or.count_set_bits(), 6);
+}
+
+#[test]
+fn partial_eq_with_same_and_different_counts() {
+    let a = BitArray::from_fn(1000, |i| i % 3 == 0);
+    let mut b = a.clone();
+    assert_eq!(a, b);
+
+    b.set(1);
+    assert_ne!(a.count_set_bits(), b.count_set_bits());
+    assert_ne!(a, b);
+
+    b.unset(0);
+    assert_eq!(a.count_set_bits(), b.count_set_bits());
+    assert_ne!(a, b);
+
+    b.set(0);
+    b.unset(1);
+    assert_eq!(a, b);
+}
+
+#[test]
+fn partial_eq_rejects_on_count_before_atoms() {
+    let a = BitArray::from_indices(100, &[5, 70]);
+    let mut b = BitArray::new(100);
+    b.atoms_mut().copy_from_slice(a.atoms());
+
+    // The atoms are identical, only the stale cached count differs, so inequality can
+    // only come from the count comparison.
+    assert_eq!(a.atoms(), b.atoms());
+    assert_ne!(a.count_set_bits(), b.count_set_bits());
+    assert_ne!(a, b);
+
+    b.recount_set_bits();
+    assert_eq!(a, b);
+}
+
+#[test]
+fn nth_unset_bit() {
+    let array = BitArray::from_fn(100, |i| i % 4 != 0 || i == 64);
+    let unset: Vec<usize> = (0..100).filter(|&i| !array.get(i)).collect();
+    assert_eq!(unset.len(), 24);
+
+    assert_eq!(array.nth_unset_bit(0), Some(0));
+    assert_eq!(array.nth_unset_bit(15), Some(60));
+    assert_eq!(array.nth_unset_bit(16), Some(68));
+    assert_eq!(array.nth_unset_bit(23), Some(96));
+    assert_eq!(array.nth_unset_bit(24), None);
+    for (n, &index) in unset.iter().enumerate() {
+        assert_eq!(array.nth_unset_bit(n), Some(index));
+    }
+
+    assert_eq!(BitArray::with_all_set(70).nth_unset_bit(0), None);
+    assert_eq!(BitArray::new(70).nth_unset_bit(69), Some(69));
+    assert_eq!(BitArray::new(70).nth_unset_bit(70), None);
+}
+
+#[test]
+fn rank0() {
+    let array = BitArray::from_fn(150, |i| (i * 7) % 5 < 2);
+
+    assert_eq!(array.rank0(0), 0);
+    assert_eq!(array.rank0(150), array.count_unset_bits());
+    assert_eq!(array.count_unset_bits(), 150 - array.count_set_bits());
+    for index in 0..=150 {
+        let ones = (0..index).filter(|&i| array.get(i)).count();
+        assert_eq!(array.rank0(index), index - ones, "index {index}");
+    }
+
+    let full = BitArray::with_all_set(64);
+    assert_eq!(full.rank0(64), 0);
+    assert_eq!(BitArray::new(64).rank0(64), 64);
+}
+
+#[test]
+fn atoms_masked_borrowed_and_owned() {
+    use std::borrow::Cow;
+
+    let mut array = BitArray::from_indices(70, &[1, 69]);
+    let masked = array.atoms_masked();
+    assert!(matches!(masked, Cow::Borrowed(_)));
+    assert_eq!(&*masked, &[0b10, 1 << 5]);
+
+    array.atoms_mut()[1] |= 1 << 63;
+    let masked = array.atoms_masked();
+    assert!(matches!(masked, Cow::Owned(_)));
+    assert_eq!(&*masked, &[0b10, 1 << 5]);
+    assert_eq!(array.atoms()[1], 1 << 63 | 1 << 5);
+}
+
+#[test]
+fn split_first_set_peels_in_order() {
+    let original = BitArray::from_indices(100, &[64, 2, 99, 40]);
+
+    let mut peeled = Vec::new();
+    let mut current = original.clone();
+    while let Some((index, rest)) = current.split_first_set() {
+        peeled.push(index);
+        current = rest;
+    }
+
+    assert_eq!(peeled, vec![2, 40, 64, 99]);
+    assert!(current.is_empty());
+    assert_eq!(original.count_set_bits(), 4);
+}
+
+#[test]
+fn on_grow_reports_old_and_new_size() {
+    use std::sync::{Arc, Mutex};
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let mut array = BitArray::new(63);
+    let recorded = Arc::clone(&events);
+    array.set_on_grow(Box::new(move |old, new| {
+        recorded.lock().unwrap().push((old, new));
+    }));
+
+    array.push(true);
+    array.push(false);
+    array.resize(200, true);
+    array.resize(100, false);
+    array.append(&BitArray::new(28));
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![(63, 64), (64, 65), (65, 200), (100, 128)]
+    );
+
+    let mut copy = array.clone();
+    copy.push(true);
+    assert_eq!(events.lock().unwrap().len(), 4);
+}
+
+#[test]
+fn write_set_indices_as_le_u32() {
+    let array = BitArray::from_indices(300, &[0, 63, 64, 299]);
+    let mut out = Vec::new();
+    array.write_set_indices(&mut out).unwrap();
+
+    assert_eq!(out.len(), 16);
+    let indices: Vec<usize> = out
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()) as usize)
+        .collect();
+    assert_eq!(indices, array.collect_set_bits());
+
+    let mut empty = Vec::new();
+    BitArray::new(10).write_set_indices(&mut empty).unwrap();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn set_indices_from_reader_round_trip() {
+    let original = BitArray::from_indices(200, &[1, 64, 65, 199]);
+    let mut bytes = Vec::new();
+    original.write_set_indices(&mut bytes).unwrap();
+
+    let mut array = original.clone();
+    array.reset();
+    array
+        .set_indices_from_reader(&mut bytes.as_slice())
+        .unwrap();
+    assert_eq!(array, original);
+}
+
+#[test]
+fn set_indices_from_reader_rejects_bad_input() {
+    use std::io::ErrorKind;
+
+    let mut array = BitArray::new(10);
+    let out_of_range = 10u32.to_le_bytes();
+    let err = array
+        .set_indices_from_reader(&mut out_of_range.as_slice())
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    let truncated = [3u8, 0, 0, 0, 5, 0];
+    let err = array
+        .set_indices_from_reader(&mut truncated.as_slice())
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(array.collect_set_bits(), vec![3]);
+}
+
+#[test]
+fn out_of_bounds_panic_names_index_and_bit_count() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    fn message(result: std::thread::Result<()>) -> String {
+        let payload = result.unwrap_err();
+        payload
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    let mut array = BitArray::new(32);
+    let expected = "bit index 40 out of bounds for BitArray of 32 bits";
+
+    let get = catch_unwind(|| {
+        let _ = array.get(40);
+    });
+    assert_eq!(message(get), expected);
+    let set = catch_unwind(AssertUnwindSafe(|| array.set(40)));
+    assert_eq!(message(set), expected);
+    let unset = catch_unwind(AssertUnwindSafe(|| array.unset(40)));
+    assert_eq!(message(unset), expected);
+    let set_bit = catch_unwind(AssertUnwindSafe(|| array.set_bit(40, true)));
+    assert_eq!(message(set_bit), expected);
+    let index = catch_unwind(|| {
+        let _ = array[32];
+    });
+    assert!(message(index).contains("bit index 32 out of bounds for BitArray of 32 bits"));
+}
+
+#[test]
+fn sparse_round_trips() {
+    let mut dense = BitArray::new(10_000);
+    for index in (7..10_000).step_by(997) {
+        dense.set(index);
+    }
+    let sparse = dense.to_sparse();
+    assert_eq!(sparse.len(), dense.count_set_bits());
+    assert!(sparse.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(BitArray::from_sparse(10_000, &sparse), dense);
+
+    let from_sparse = BitArray::from_sparse(5000, &[4999, 12, 12, 0, 4999]);
+    assert_eq!(from_sparse.count_set_bits(), 3);
+    assert_eq!(from_sparse.to_sparse(), vec![0, 12, 4999]);
+}
+
+#[test]
+#[should_panic(expected = "bit index 64 out of bounds for BitArray of 64 bits")]
+fn from_sparse_out_of_bounds() {
+    let _ = BitArray::from_sparse(64, &[1, 64]);
+}
+
+#[test]
+fn debug_matches_bit_by_bit_format() {
+    fn reference(array: &BitArray) -> String {
+        let mut out = String::new();
+        for i in 0..array.bit_count() {
+            if i > 0 && i % 8 == 0 {
+                out.push(' ');
+            }
+            out.push(if array.get(i) { '1' } else { '0' });
+        }
+        out
+    }
+
+    for bit_count in [1, 7, 8, 9, 63, 64, 65, 100, 128, 1000] {
+        let array = BitArray::from_fn(bit_count, |i| i % 3 == 0 || i % 7 == 1);
+        assert_eq!(
+            format!("{array:?}"),
+            reference(&array),
+            "bit_count {bit_count}"
+        );
+    }
+}
+
+#[test]
+fn replace_returns_previous_value() {
+    let mut array = BitArray::new(70);
+
+    assert!(!array.replace(65, false));
+    assert_eq!(array.count_set_bits(), 0);
+    assert!(!array.replace(65, true));
+    assert_eq!(array.count_set_bits(), 1);
+    assert!(array.replace(65, true));
+    assert_eq!(array.count_set_bits(), 1);
+    assert!(array.replace(65, false));
+    assert_eq!(array.count_set_bits(), 0);
+    assert!(!array.get(65));
+}
+
+#[test]
+fn capacity_bits_covers_bit_count() {
+    let mut array = BitArray::new(10);
+    for _ in 0..300 {
+        assert!(array.capacity_bits() >= array.bit_count());
+        assert_eq!(array.capacity_bits() % 64, 0);
+        array.push(true);
+    }
+
+    array.reserve(1000);
+    assert!(array.capacity_bits() >= array.bit_count() + 1000);
+}
+
+#[test]
+fn first_unset_bit_is_none_for_full_partial_atom() {
+    for bit_count in [1, 10, 33, 63, 65, 130] {
+        let mut array = BitArray::new(bit_count);
+        for i in 0..bit_count {
+            array.set(i);
+        }
+        assert_eq!(array.first_unset_bit(), None, "bit_count {bit_count}");
+        assert_eq!(BitArray::with_all_set(bit_count).first_unset_bit(), None);
+
+        let mut grown = BitArray::new(1);
+        grown.set(0);
+        grown.resize(bit_count.max(2), true);
+        assert_eq!(grown.first_unset_bit(), None, "bit_count {bit_count}");
+    }
+}
+
+#[test]
+fn all_set_verified_detects_stale_count() {
+    let mut array = BitArray::with_all_set(70);
+    assert!(array.all_set());
+    assert!(array.all_set_verified());
+
+    array.atoms_mut()[1] &= !(1 << 3);
+    assert!(array.all_set());
+    assert!(!array.all_set_verified());
+
+    array.recount_set_bits();
+    assert!(!array.all_set());
+    assert!(!array.all_set_verified());
+}
+
+#[test]
+fn next_change_from_across_atom_boundary() {
+    let mut array = BitArray::new(200);
+    for i in 60..130 {
+        array.set(i);
+    }
+
+    assert_eq!(array.next_change_from(0), Some(60));
+    assert_eq!(array.next_change_from(59), Some(60));
+    assert_eq!(array.next_change_from(60), Some(130));
+    assert_eq!(array.next_change_from(63), Some(130));
+    assert_eq!(array.next_change_from(129), Some(130));
+    assert_eq!(array.next_change_from(130), None);
+    assert_eq!(array.next_change_from(199), None);
+
+    let mut starts = vec![0];
+    while let Some(next) = array.next_change_from(*starts.last().unwrap()) {
+        starts.push(next);
+    }
+    assert_eq!(starts, vec![0, 60, 130]);
+
+    let full = BitArray::with_all_set(70);
+    assert_eq!(full.next_change_from(0), None);
+}
+
+#[test]
+fn collect_from_bools_allocates_once() {
+    let values: Vec<bool> = (0..10_000).map(|i| i % 3 == 0 || i % 64 == 63).collect();
+    let array: BitArray = values.iter().copied().collect();
+
+    assert_eq!(array.bit_count(), 10_000);
+    let expected_bits = 10_000usize.div_ceil(64) * 64;
+    assert!(array.capacity_bits() >= expected_bits);
+    assert!(array.capacity_bits() < 2 * expected_bits);
+    assert_eq!(
+        array.count_set_bits(),
+        values.iter().filter(|&&v| v).count()
+    );
+    assert!(values.iter().enumerate().all(|(i, &v)| array.get(i) == v));
+
+    let unknown_len: BitArray = (0..100)
+        .filter(|i| i % 2 == 0)
+        .map(|i| i % 4 == 0)
+        .collect();
+    assert_eq!(unknown_len.bit_count(), 50);
+    assert_eq!(unknown_len.count_set_bits(), 25);
+}
+
+#[test]
+#[should_panic(expected = "bit_count must be greater than zero")]
+fn collect_from_empty_iterator() {
+    let _: BitArray = std::iter::empty().collect();
+}
+
+#[test]
+fn write_binary_matches_display_and_debug() {
+    let array = BitArray::from_indices(70, &[0, 9, 64, 69]);
+
+    let mut plain = Vec::new();
+    array.write_binary(&mut plain, false).unwrap();
+    assert_eq!(String::from_utf8(plain).unwrap(), format!("{array}"));
+
+    let mut grouped = Vec::new();
+    array.write_binary(&mut grouped, true).unwrap();
+    assert_eq!(String::from_utf8(grouped).unwrap(), format!("{array:?}"));
+}
+
+#[test]
+fn count_tracking_can_be_disabled_and_restored() {
+    let mut array = BitArray::new(130);
+    array.set_count_tracking(false);
+    for i in (0..130).step_by(2) {
+        array.set(i);
+    }
+    array.unset(0);
+    array.atoms_mut()[2] = 0b11;
+    assert_eq!(array.count_set_bits(), 64 + 1);
+
+    array.set_count_tracking(true);
+    assert_eq!(array.count_set_bits(), 65);
+    array.unset(2);
+    assert_eq!(array.count_set_bits(), 64);
+    assert_eq!(array.count_set_bits(), array.collect_set_bits().len());
+}
+
+#[test]
+fn or_at_composites_at_offset() {
+    let layer = BitArray::from_indices(70, &[0, 5, 63, 64, 69]);
+
+    for offset in [0, 64, 13, 130] {
+        let mut canvas = BitArray::from_indices(200, &[1, 199]);
+        canvas.or_at(&layer, offset);
+
+        let mut expected = vec![1, 199];
+        expected.extend(layer.iter_set_bits().map(|i| i + offset));
+        expected.sort_unstable();
+        expected.dedup();
+        assert_eq!(canvas.collect_set_bits(), expected, "offset {offset}");
+        assert_eq!(canvas.count_set_bits(), expected.len());
+    }
+}
+
+#[test]
+#[should_panic(expected = "Destination range out of bounds")]
+fn or_at_out_of_bounds() {
+    let mut canvas = BitArray::new(100);
+    canvas.or_at(&BitArray::new(10), 91);
+}
+
+#[test]
+fn find_unset_run_aligned_skips_unaligned_gaps() {
+    let mut array = BitArray::with_all_set(256);
+    for i in 3..16 {
+        array.unset(i);
+    }
+    assert_eq!(array.find_unset_run(8), Some(3));
+    assert_eq!(array.find_unset_run_aligned(8, 8), Some(8));
+    assert_eq!(array.find_unset_run_aligned(9, 8), None);
+    assert_eq!(array.find_unset_run_aligned(8, 16), None);
+
+    for i in 128..160 {
+        array.unset(i);
+    }
+    assert_eq!(array.find_unset_run_aligned(16, 16), Some(128));
+    assert_eq!(array.find_unset_run_aligned(32, 64), Some(128));
+    assert_eq!(array.find_unset_run_aligned(4, 1), Some(3));
+    assert_eq!(array.find_unset_run_aligned(0, 64), Some(0));
+}
+
+#[test]
+#[should_panic(expected = "align must be greater than zero")]
+fn find_unset_run_aligned_zero_align() {
+    let _ = BitArray::new(8).find_unset_run_aligned(1, 0);
+}
+
+#[test]
+fn try_set_range_validates_bounds() {
+    use bit_array_rs::BitArrayError;
+
+    let mut array = BitArray::from_indices(100, &[0]);
+    array.try_set_range(60, 70).unwrap();
+    assert_eq!(array.count_set_bits(), 11);
+    assert!((60..70).all(|i| array.get(i)));
+    array.try_set_range(99, 99).unwrap();
+    assert_eq!(array.count_set_bits(), 11);
+
+    let before = array.clone();
+    assert_eq!(
+        array.try_set_range(50, 40),
+        Err(BitArrayError::RangeOutOfBounds {
+            start: 50,
+            end: 40,
+            bit_count: 100
+        })
+    );
+    assert_eq!(
+        array.try_set_range(90, 101),
+        Err(BitArrayError::RangeOutOfBounds {
+            start: 90,
+            end: 101,
+            bit_count: 100
+        })
+    );
+    assert_eq!(array, before);
+}
+
+#[test]
+#[should_panic(expected = "bit range 90..101 out of bounds for BitArray of 100 bits")]
+fn set_range_out_of_bounds() {
+    BitArray::new(100).set_range(90, 101);
+}
+
+#[test]
+fn pad_to_multiple_of_grows_to_next_multiple() {
+    let mut array = BitArray::from_indices(10, &[0, 9]);
+    array.pad_to_multiple_of(8, true);
+    assert_eq!(array.bit_count(), 16);
+    assert_eq!(array.count_set_bits(), 8);
+    assert_eq!(array.collect_set_bits(), vec![0, 9, 10, 11, 12, 13, 14, 15]);
+
+    array.pad_to_multiple_of(8, false);
+    assert_eq!(array.bit_count(), 16);
+
+    array.pad_to_multiple_of(64, false);
+    assert_eq!(array.bit_count(), 64);
+    assert_eq!(array.count_set_bits(), 8);
+}
+
+#[test]
+#[should_panic(expected = "multiple must be greater than zero")]
+fn pad_to_multiple_of_zero() {
+    BitArray::new(10).pad_to_multiple_of(0, false);
+}
+
+#[test]
+fn as_byte_slice_is_equal_for_equal_arrays() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash(bytes: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        bytes.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let a = BitArray::from_indices(70, &[0, 8, 69]);
+    let mut b = BitArray::with_all_set(70);
+    b.resize(100, true);
+    b.resize(70, false);
+    for i in 0..70 {
+        b.set_bit(i, a.get(i));
+    }
+
+    assert_eq!(a.as_byte_slice().len(), 16);
+    assert_eq!(a.as_byte_slice(), b.as_byte_slice());
+    assert_eq!(hash(a.as_byte_slice()), hash(b.as_byte_slice()));
+
+    #[cfg(target_endian = "little")]
+    assert_eq!(&a.as_byte_slice()[..9], a.to_bytes().as_slice());
+}
+
+#[test]
+fn set_strided_sets_every_kth_bit() {
+    let mut array = BitArray::new(100);
+    array.set_strided(0, 10, 3);
+    assert_eq!(array.collect_set_bits(), vec![0, 3, 6, 9]);
+    assert_eq!(array.count_set_bits(), 4);
+
+    array.set_strided(60, 100, 16);
+    assert_eq!(array.collect_set_bits(), vec![0, 3, 6, 9, 60, 76, 92]);
+    array.set_strided(5, 5, 1);
+    assert_eq!(array.count_set_bits(), 7);
+}
+
+#[test]
+#[should_panic(expected = "stride must be greater than zero")]
+fn set_strided_zero_stride() {
+    BitArray::new(10).set_strided(0, 10, 0);
+}
+
+#[test]
+#[should_panic(expected = "bit range 0..11 out of bounds for BitArray of 10 bits")]
+fn set_strided_out_of_bounds() {
+    BitArray::new(10).set_strided(0, 11, 2);
+}
+
+#[test]
+fn invert_range_twice_restores_original() {
+    let original = BitArray::from_fn(200, |i| i % 5 == 0);
+    let mut array = original.clone();
+
+    array.invert_range(3, 150);
+    let expected: Vec<usize> = (0..200)
+        .filter(|&i| (i % 5 == 0) != (3..150).contains(&i))
+        .collect();
+    assert_eq!(array.collect_set_bits(), expected);
+    assert_eq!(array.count_set_bits(), expected.len());
+
+    array.invert_range(3, 150);
+    assert_eq!(array, original);
+    assert_eq!(array.count_set_bits(), original.count_set_bits());
+
+    array.invert_range(0, 200);
+    assert_eq!(array.count_set_bits(), 200 - original.count_set_bits());
+    array.invert_range(64, 64);
+    assert_eq!(array.count_set_bits(), 200 - original.count_set_bits());
+}
+
+#[test]
+#[should_panic(expected = "bit range 5..4 out of bounds for BitArray of 10 bits")]
+fn invert_range_reversed() {
+    BitArray::new(10).invert_range(5, 4);
+}
+
+#[test]
+fn index_references_are_shared_across_arrays() {
+    let a = BitArray::from_indices(8, &[1]);
+    let b = BitArray::from_indices(200, &[150]);
+
+    assert!(std::ptr::eq(&a[1], &b[150]));
+    assert!(std::ptr::eq(&a[0], &b[199]));
+    assert!(!std::ptr::eq(&a[1], &b[0]));
+    assert!(a[1] && !b[0]);
+}
+
+#[test]
+fn common_prefix_len_stops_at_first_difference() {
+    let a = BitArray::from_indices(130, &[1, 64, 100]);
+    assert_eq!(a.common_prefix_len(&a.clone()), 130);
+
+    let mut b = a.clone();
+    b.set(0);
+    assert_eq!(a.common_prefix_len(&b), 0);
+
+    let mut c = a.clone();
+    c.unset(100);
+    assert_eq!(a.common_prefix_len(&c), 100);
+    assert_eq!(c.common_prefix_len(&a), 100);
+
+    let longer = a.clone_resized(200, true);
+    assert_eq!(a.common_prefix_len(&longer), 130);
+    let shorter = BitArray::from_indices(70, &[1, 64]);
+    assert_eq!(a.common_prefix_len(&shorter), 70);
+}
+
+#[test]
+fn shift_in_streams_through_register() {
+    for bit_count in [5, 64, 70] {
+        let mut register = BitArray::new(bit_count);
+        let input: Vec<bool> = (0..300).map(|i| i % 3 == 0 || i % 7 == 0).collect();
+
+        for (step, &bit) in input.iter().enumerate() {
+            let expected_out = step >= bit_count && input[step - bit_count];
+            assert_eq!(register.shift_in(bit), expected_out, "step {step}");
+
+            let expected: Vec<usize> = (0..bit_count.min(step + 1))
+                .filter(|&i| input[step - i])
+                .collect();
+            assert_eq!(register.collect_set_bits(), expected, "step {step}");
+            assert_eq!(register.count_set_bits(), expected.len());
+        }
+    }
+}
+
+#[test]
+fn iter_from_matches_skip() {
+    let array = BitArray::from_fn(130, |i| i % 3 == 1);
+    for start in [0, 5, 64, 129, 130] {
+        let from: Vec<bool> = array.iter_from(start).collect();
+        let skipped: Vec<bool> = array.iter().skip(start).collect();
+        assert_eq!(from, skipped, "start {start}");
+        assert_eq!(array.iter_from(start).len(), 130 - start);
+    }
+    assert_eq!(
+        array.iter_from(126).rev().collect::<Vec<_>>(),
+        vec![false, false, true, false]
+    );
+}
+
+#[test]
+#[should_panic(expected = "start 11 out of bounds for BitArray of 10 bits")]
+fn iter_from_out_of_bounds() {
+    let _ = BitArray::new(10).iter_from(11);
+}
+
+#[test]
+fn fold_atoms_sees_masked_atoms() {
+    let mut array = BitArray::from_indices(130, &[0, 64, 65, 129]);
+    array.atoms_mut()[2] |= 1 << 10;
+
+    let xor = array.fold_atoms(0, |acc, atom| acc ^ atom);
+    let mut expected = 0;
+    for (i, &atom) in array.atoms().iter().enumerate() {
+        expected ^= if i == 2 { atom & 0b11 } else { atom };
+    }
+    assert_eq!(xor, expected);
+    assert_eq!(xor, 1 ^ 0b11 ^ 0b10);
+
+    let popcount = array.fold_atoms(0, |acc, atom| acc + atom.count_ones());
+    assert_eq!(popcount, 4);
+}
+
+#[test]
+fn is_canonical_detects_broken_invariants() {
+    let mut array = BitArray::from_indices(70, &[3, 69]);
+    assert!(array.is_canonical());
+    array.resize(200, true);
+    array.append(&BitArray::with_all_set(5));
+    assert!(array.is_canonical());
+
+    let mut padded = BitArray::from_indices(70, &[3, 69]);
+    padded.atoms_mut()[1] |= 1 << 20;
+    padded.recount_set_bits();
+    assert!(!padded.is_canonical());
+    padded.normalize();
+    assert!(padded.is_canonical());
+
+    let mut stale = BitArray::from_indices(70, &[3]);
+    stale.atoms_mut()[0] |= 1;
+    assert!(!stale.is_canonical());
+    stale.recount_set_bits();
+    assert!(stale.is_canonical());
+}
+
+#[test]
+fn reserve_exact_and_reset_keep_capacity() {
+    let mut array = BitArray::new(64);
+    array.reserve_exact(640);
+    assert!(array.capacity_bits() >= 704);
+    let capacity = array.capacity_bits();
+
+    for i in 0..640 {
+        array.push(i % 2 == 0);
+    }
+    assert_eq!(array.capacity_bits(), capacity);
+
+    array.reset();
+    assert_eq!(array.capacity_bits(), capacity);
+    assert_eq!(array.bit_count(), 704);
+    assert!(array.is_empty());
+}
+
+#[test]
+fn set_bit_grow_grows_to_min_bit_count() {
+    let mut array = BitArray::from_indices(10, &[2]);
+    array.set_bit_grow(100, true, 256);
+    assert_eq!(array.bit_count(), 256);
+    assert_eq!(array.collect_set_bits(), vec![2, 100]);
+
+    array.set_bit_grow(300, true, 0);
+    assert_eq!(array.bit_count(), 301);
+    array.set_bit_grow(5, true, 64);
+    assert_eq!(array.bit_count(), 301);
+    array.set_bit_grow(2, false, 0);
+    assert_eq!(array.collect_set_bits(), vec![5, 100, 300]);
+    assert_eq!(array.count_set_bits(), 3);
+}
+
+#[test]
+fn set_all_on_exact_atom_multiples() {
+    for bit_count in [1, 31, 32, 33, 63, 64, 65, 128] {
+        let mut array = BitArray::from_indices(bit_count, &[0]);
+        array.set_all();
+        assert_eq!(array.count_set_bits(), bit_count, "bit_count {bit_count}");
+        assert!(array.all_set_verified());
+        assert!(array.is_canonical());
+        assert_eq!(array.first_unset_bit(), None);
+        assert_eq!(array, BitArray::with_all_set(bit_count));
+
+        let from_atoms = BitArray::from_atoms(bit_count, array.atoms());
+        assert_eq!(from_atoms.count_set_bits(), bit_count);
+    }
+
+    let mut exact = BitArray::new(64);
+    exact.set_all();
+    assert_eq!(exact.atoms(), &[u64::MAX]);
+    exact.normalize();
+    assert_eq!(exact.count_set_bits(), 64);
+}
+
+#[test]
+fn to_rle_coalesced_is_canonical() {
+    let edited = BitArray::from_rle(&[(true, 3), (true, 61), (false, 0), (false, 6), (true, 130)]);
+    let pairs = edited.to_rle_coalesced();
+
+    assert_eq!(pairs, vec![(true, 64), (false, 6), (true, 130)]);
+    assert!(pairs.iter().all(|&(_, length)| length > 0));
+    assert!(pairs.windows(2).all(|pair| pair[0].0 != pair[1].0));
+    assert_eq!(pairs.iter().map(|&(_, length)| length).sum::<usize>(), 200);
+    assert_eq!(BitArray::from_rle(&pairs), edited);
+
+    let single = BitArray::new(65).to_rle_coalesced();
+    assert_eq!(single, vec![(false, 65)]);
+}
+
+#[test]
+fn bit_index_matches_usize_path() {
+    use bit_array_rs::BitIndex;
+
+    let mut typed = BitArray::new(130);
+    let mut plain = BitArray::new(130);
+    for i in [0, 63, 64, 129] {
+        typed.set(BitIndex(i));
+        plain.set(i);
+    }
+    typed.unset(BitIndex(63));
+    plain.unset(63);
+    typed.set_bit(BitIndex(100), true);
+    plain.set_bit(100, true);
+
+    assert_eq!(typed, plain);
+    for i in 0..130 {
+        assert_eq!(typed.get(BitIndex(i)), plain.get(i));
+    }
+    assert_eq!(typed.count_set_bits(), 4);
+}
+
+#[test]
+fn partial_eq_compares_atoms_when_count_is_untracked() {
+    let tracked = BitArray::from_indices(100, &[5, 70]);
+    let mut untracked = BitArray::new(100);
+    untracked.set_count_tracking(false);
+    untracked.atoms_mut().copy_from_slice(tracked.atoms());
+
+    assert_eq!(untracked, tracked);
+    assert_eq!(tracked, untracked);
+    untracked.set(6);
+    assert_ne!(untracked, tracked);
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_count_set_bits_matches_serial_count() {
+    for bit_count in [1, 63, 64, 65, 130, 64 * 4096 + 7, 3 * 64 * 4096] {
+        let mut array = BitArray::from_fn(bit_count, |i| i % 3 == 0 || i % 7 == 0);
+        assert_eq!(array.par_count_set_bits(), array.count_set_bits());
+
+        array.set_count_tracking(false);
+        assert_eq!(array.par_count_set_bits(), array.count_set_bits());
+    }
+}
+
+#[cfg(feature = "bitvec")]
+#[test]
+fn bitvec_round_trip() {
+    for bit_count in [1, 63, 64, 65, 100, 200] {
+        let array = BitArray::from_fn(bit_count, |i| i % 3 == 0 || i % 7 == 1);
+        let bits = array.to_bitvec();
+
+        assert_eq!(bits.len(), bit_count);
+        for i in 0..bit_count {
+            assert_eq!(bits[i], array.get(i), "bit {i} of {bit_count}");
+        }
+        assert_eq!(BitArray::from_bitvec(&bits), array);
+    }
+}
+
+#[cfg(feature = "bitvec")]
+#[test]
+fn from_bitvec_is_lsb_first() {
+    let bits: bitvec::vec::BitVec = (0..10).map(|i| i == 0 || i == 3 || i == 9).collect();
+    let array = BitArray::from_bitvec(&bits);
+
+    assert_eq!(array.bit_count(), 10);
+    assert_eq!(array.iter_set_bits().collect::<Vec<_>>(), [0, 3, 9]);
+    assert_eq!(array.to_bytes(), [0b0000_1001, 0b10]);
+}